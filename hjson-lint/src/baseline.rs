@@ -0,0 +1,322 @@
+//! Baselines of existing lints, so that only new lints are reported.
+//!
+//! A baseline records the lints found in each file by their rule and a
+//! fingerprint of the line they're on, ignoring indentation. Lints still match
+//! the baseline when lines are added or removed above them, but not once the
+//! line itself changes. Identical lines are counted, so adding another copy of
+//! a line with a recorded lint reports the new copy.
+//!
+//! Baselines are written as JSON, sorted so that they diff well:
+//!
+//! ```json
+//! {
+//!   "lints": [
+//!     {
+//!       "count": 1,
+//!       "file": "config.hjson",
+//!       "fingerprint": "074975fe6f85c1e9",
+//!       "rule": "trailing-comma"
+//!     }
+//!   ],
+//!   "version": 1
+//! }
+//! ```
+//!
+//! Syntax errors are never recorded, so they're always reported.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde_json::{json, Value};
+
+use crate::linter::Lint;
+use crate::render::Report;
+
+/// Version of the baseline format written.
+const VERSION: u64 = 1;
+
+/// Lints which have been accepted, by file, rule, and fingerprint.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Baseline {
+    counts: BTreeMap<Key, usize>,
+}
+
+/// What a lint in a baseline is identified by. Files are named as they're
+/// reported, without any leading `./`.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Key {
+    file: String,
+    rule: String,
+    fingerprint: String,
+}
+
+impl Key {
+    fn new(file: &str, source: &str, lint: &Lint) -> Self {
+        Key {
+            file: file.trim_start_matches("./").to_string(),
+            rule: lint.kind().name().to_string(),
+            fingerprint: fingerprint(source, lint),
+        }
+    }
+}
+
+impl Baseline {
+    /// A baseline with every lint in the given reports.
+    pub fn new(reports: &[Report]) -> Self {
+        let mut baseline = Self::default();
+
+        for report in reports {
+            for lint in &report.lints {
+                let key = Key::new(&report.file, &report.source, lint);
+                *baseline.counts.entry(key).or_default() += 1;
+            }
+        }
+
+        baseline
+    }
+
+    /// Number of lints in the baseline.
+    pub fn len(&self) -> usize {
+        self.counts.values().sum()
+    }
+
+    /// Whether the baseline has no lints.
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Remove the lints in the baseline from the given reports.
+    pub fn filter(&self, reports: &mut [Report]) {
+        let mut remaining = self.counts.clone();
+
+        for report in reports {
+            let (file, source) = (&report.file, &report.source);
+            report.lints.retain(
+                |lint| match remaining.get_mut(&Key::new(file, source, lint)) {
+                    Some(count) if *count > 0 => {
+                        *count -= 1;
+                        false
+                    }
+                    _ => true,
+                },
+            );
+        }
+    }
+
+    /// Parse a baseline written by [`Baseline::to_json`].
+    pub fn parse(json: &str) -> Result<Self, BaselineError> {
+        let value: Value =
+            serde_json::from_str(json).map_err(|err| BaselineError::Parse(err.to_string()))?;
+        let invalid = |message: &str| BaselineError::Parse(message.to_string());
+
+        match value["version"].as_u64() {
+            Some(VERSION) => (),
+            Some(version) => return Err(BaselineError::Version(version)),
+            None => return Err(invalid("missing `version`")),
+        }
+
+        let lints = value["lints"]
+            .as_array()
+            .ok_or_else(|| invalid("`lints` is not an array"))?;
+
+        let mut baseline = Self::default();
+        for lint in lints {
+            let field = |name: &str| {
+                lint[name]
+                    .as_str()
+                    .map(String::from)
+                    .ok_or_else(|| invalid(&format!("lint without a string `{name}`")))
+            };
+            let key = Key {
+                file: field("file")?,
+                rule: field("rule")?,
+                fingerprint: field("fingerprint")?,
+            };
+            let count = lint["count"]
+                .as_u64()
+                .ok_or_else(|| invalid("lint without a numeric `count`"))?;
+
+            *baseline.counts.entry(key).or_default() += count as usize;
+        }
+
+        Ok(baseline)
+    }
+
+    /// Read and parse a baseline file.
+    pub fn load(path: &Path) -> Result<Self, BaselineError> {
+        let json = fs::read_to_string(path).map_err(BaselineError::Io)?;
+        Self::parse(&json)
+    }
+
+    /// The baseline as pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        let lints: Vec<Value> = self
+            .counts
+            .iter()
+            .map(|(key, count)| {
+                json!({
+                    "file": key.file,
+                    "rule": key.rule,
+                    "fingerprint": key.fingerprint,
+                    "count": count,
+                })
+            })
+            .collect();
+
+        let json = json!({ "version": VERSION, "lints": lints });
+        let mut json = serde_json::to_string_pretty(&json).expect("JSON values always serialize");
+        json.push('\n');
+        json
+    }
+}
+
+/// Fingerprint of the line a lint starts on, ignoring surrounding whitespace.
+fn fingerprint(source: &str, lint: &Lint) -> String {
+    let offset = lint.span().start().byte_offset.min(source.len());
+    let start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let end = source[offset..]
+        .find('\n')
+        .map_or(source.len(), |i| offset + i);
+
+    format!("{:016x}", fnv1a(source[start..end].trim().as_bytes()))
+}
+
+/// The 64-bit FNV-1a hash, which unlike the standard library's hashers is
+/// stable across releases.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Errors from loading baselines.
+#[derive(Debug)]
+pub enum BaselineError {
+    /// The file could not be read.
+    Io(io::Error),
+    /// The file is not a valid baseline.
+    Parse(String),
+    /// The file was written in an unsupported version of the format.
+    Version(u64),
+}
+
+impl Display for BaselineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BaselineError::Io(err) => write!(f, "{err}"),
+            BaselineError::Parse(message) => write!(f, "invalid baseline: {message}"),
+            BaselineError::Version(version) => {
+                write!(f, "unsupported baseline version {version}")
+            }
+        }
+    }
+}
+
+impl Error for BaselineError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::linter::{Config, Linter};
+    use indoc::indoc;
+
+    fn report(file: &str, source: &str) -> Report {
+        Report {
+            file: file.to_string(),
+            source: source.to_string(),
+            errors: Vec::new(),
+            lints: Linter::lint(Config::strict(), source).unwrap(),
+        }
+    }
+
+    fn rules(reports: &[Report]) -> Vec<(&str, &'static str, usize)> {
+        reports
+            .iter()
+            .flat_map(|report| {
+                report.lints.iter().map(|lint| {
+                    let line = lint.span().start().line;
+                    (report.file.as_str(), lint.kind().name(), line)
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn filter() {
+        let before = indoc! {r#"
+            {
+              "a": [
+                1,
+              ],
+              "b": 2,
+            }
+        "#};
+        let baseline = Baseline::new(&[report("./a.hjson", before)]);
+        assert_eq!(baseline.len(), 2);
+
+        // Lines moving, even to other indentation, still match, but only as
+        // many times as they were recorded.
+        let after = indoc! {r#"
+            {
+              "new": 0,
+              "c": {
+                "a": [
+                  1,
+                ],
+                "b": 2,
+              },
+              "d": [
+                1,
+              ]
+            }
+        "#};
+        let mut reports = [report("a.hjson", after), report("b.hjson", before)];
+        baseline.filter(&mut reports);
+
+        assert_eq!(
+            rules(&reports),
+            [
+                ("a.hjson", "trailing-comma", 10),
+                ("b.hjson", "trailing-comma", 3),
+                ("b.hjson", "trailing-comma", 5),
+            ]
+        );
+    }
+
+    #[test]
+    fn json() {
+        let baseline = Baseline::new(&[report("a.hjson", "{\n'a': 1,\n'a': 2,\n}")]);
+        let json = baseline.to_json();
+
+        assert_eq!(Baseline::parse(&json).unwrap(), baseline);
+        assert_eq!(
+            serde_json::from_str::<Value>(&json).unwrap()["lints"][0],
+            json!({
+                "file": "a.hjson",
+                "rule": "duplicate-key",
+                "fingerprint": format!("{:016x}", fnv1a(b"'a': 2,")),
+                "count": 1,
+            })
+        );
+
+        let err = Baseline::parse(r#"{"version": 2, "lints": []}"#).unwrap_err();
+        assert_eq!(err.to_string(), "unsupported baseline version 2");
+
+        let err = Baseline::parse(r#"{"version": 1, "lints": [{"file": "a"}]}"#).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid baseline: lint without a string `rule`"
+        );
+    }
+
+    #[test]
+    fn fnv() {
+        // Known values, so the hash can't change without noticing.
+        assert_eq!(fnv1a(b""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a(b"a"), 0xaf63dc4c8601ec8c);
+    }
+}
@@ -0,0 +1,143 @@
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::process::ExitCode;
+
+use hjson_lint::files::{self, Input};
+use hjson_lint::format::{self, Style};
+
+const USAGE: &str = "\
+Usage: hjson-fmt [OPTIONS] [PATH]...
+
+Format Hjson files in place. Each PATH may be a file, a directory to search
+recursively, a glob pattern such as 'configs/**/*.hjson', or `-` for standard
+input, which is formatted to standard output. If no paths are given, standard
+input is formatted.
+
+Options:
+      --check              Don't change any files, but list those which are
+                           not already formatted
+  -e, --extension <EXT>    Extension of files to find in directories and globs
+                           (may be repeated, defaults to `hjson`)
+      --indent <INDENT>    Number of spaces to indent by, or `tab` (defaults
+                           to 2)
+      --commas <STYLE>     Where to put commas: separate (between members),
+                           trailing (after every member), or never (defaults
+                           to separate)
+      --quotes <STYLE>     How to quote keys and strings: preserve, double, or
+                           minimal (defaults to preserve)
+      --braces <STYLE>     Whether to put braces around the whole document:
+                           preserve, always, or never (defaults to preserve)
+  -h, --help               Print this help message
+
+Exits with 1 if `--check` found unformatted files, or 2 if any file could not be
+formatted.";
+
+/// Command-line arguments.
+#[derive(Debug, Default)]
+struct Args {
+    paths: Vec<String>,
+    extensions: Vec<String>,
+    style: Style,
+    check: bool,
+    help: bool,
+}
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Args::default();
+
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("{arg} requires a value"));
+
+            match arg.as_str() {
+                "-h" | "--help" => parsed.help = true,
+                "--check" => parsed.check = true,
+                "-e" | "--extension" => parsed.extensions.push(value()?),
+                "--indent" => parsed.style.indent = value()?.parse()?,
+                "--commas" => parsed.style.commas = value()?.parse()?,
+                "--quotes" => parsed.style.quotes = value()?.parse()?,
+                "--braces" => parsed.style.braces = value()?.parse()?,
+                "--" => parsed.paths.extend(&mut args),
+                "-" => parsed.paths.push(arg),
+                _ if arg.starts_with('-') => return Err(format!("unknown option `{arg}`")),
+                _ => parsed.paths.push(arg),
+            }
+        }
+
+        if parsed.paths.is_empty() {
+            parsed.paths.push(String::from("-"));
+        }
+
+        Ok(parsed)
+    }
+}
+
+fn main() -> ExitCode {
+    let args = match Args::parse(env::args().skip(1)) {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("error: {err}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    if args.help {
+        println!("{USAGE}");
+        return ExitCode::SUCCESS;
+    }
+
+    let extensions: Vec<&str> = match args.extensions.is_empty() {
+        true => files::DEFAULT_EXTENSIONS.to_vec(),
+        false => args.extensions.iter().map(String::as_str).collect(),
+    };
+
+    let inputs = match files::find(&args.paths, &extensions) {
+        Ok(inputs) => inputs,
+        Err(err) => {
+            eprintln!("error: {err}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let mut formatted = true;
+    let mut clean = true;
+    for input in &inputs {
+        match format(input, &args) {
+            Ok(unchanged) => clean &= unchanged,
+            Err(err) => {
+                eprintln!("error: {input}: {err}");
+                formatted = false;
+            }
+        }
+    }
+
+    match (formatted, clean) {
+        (false, _) => ExitCode::from(2),
+        (true, false) => ExitCode::from(1),
+        (true, true) => ExitCode::SUCCESS,
+    }
+}
+
+/// Format a single input, returning whether it was already formatted.
+fn format(input: &Input, args: &Args) -> Result<bool, Box<dyn Error>> {
+    let source = input.read()?;
+    let output = format::format(&source, args.style)?;
+    let unchanged = output == source;
+
+    if args.check {
+        if !unchanged {
+            println!("{input}");
+        }
+        return Ok(unchanged);
+    }
+
+    match input {
+        Input::Stdin => print!("{output}"),
+        Input::File(path) if !unchanged => fs::write(path, output)?,
+        Input::File(_) => {}
+    }
+
+    // Only `--check` fails for unformatted files.
+    Ok(true)
+}
@@ -0,0 +1,314 @@
+//! Conversion of documents between Hjson and JSON.
+//!
+//! Documents are decoded by [`hjson_parser`] in both directions, since JSON is
+//! also valid Hjson. Conversion fails rather than change the data, so numbers
+//! which would lose precision and strings which can't be decoded are errors.
+
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt::{self, Display};
+
+use hjson_parser::value::{DecodeError, Number, Value, ValueKind};
+
+use crate::lexer::Cursor;
+use crate::linter::{AllowDeny, Config};
+
+/// Convert an Hjson (or JSON) document to JSON, optionally pretty-printed.
+pub fn to_json(source: &str, pretty: bool) -> Result<String, ConvertError> {
+    let value = decode(source)?;
+
+    let json = match pretty {
+        true => serde_json::to_string_pretty(&value),
+        false => serde_json::to_string(&value),
+    };
+    let mut json = json.map_err(|err| ConvertError::Write(err.to_string()))?;
+    json.push('\n');
+
+    Ok(json)
+}
+
+/// Convert a JSON (or Hjson) document to Hjson which passes `config`.
+pub fn to_hjson(source: &str, config: &Config) -> Result<String, ConvertError> {
+    let value = decode(source)?;
+
+    if config.duplicate_keys == AllowDeny::Deny {
+        check_duplicate_keys(source, &value)?;
+    }
+
+    hjson_parser::ser::to_string_with(&value, &config.serializer_options())
+        .map_err(|err| ConvertError::Write(err.to_string()))
+}
+
+/// Decode a document, checking that every value can be written again as it
+/// was.
+fn decode(source: &str) -> Result<Value, ConvertError> {
+    let value = hjson_parser::parse_value(source).map_err(|err| {
+        let start = match &err {
+            DecodeError::Syntax(errors) => errors.first().map_or(0, |error| error.range.start),
+            DecodeError::InvalidEscape(range) => range.start,
+        };
+        ConvertError::Decode {
+            message: err.to_string(),
+            at: Cursor::at(source, start),
+        }
+    })?;
+
+    check_numbers(source, &value)?;
+
+    Ok(value)
+}
+
+/// Check that every number in `value` can be written without changing it.
+///
+/// Integers are kept exactly, apart from the sign of `-0`, but other numbers
+/// are decoded as `f64`s, which can't hold every value that can be written.
+fn check_numbers(source: &str, value: &Value) -> Result<(), ConvertError> {
+    let text = &source[value.span.clone()];
+    let lossy = || ConvertError::Number {
+        text: text.to_string(),
+        at: Cursor::at(source, value.span.start),
+    };
+
+    match &value.kind {
+        ValueKind::Number(Number::F64(n))
+            if !n.is_finite() || decimal(text) != decimal(&format!("{n:?}")) =>
+        {
+            return Err(lossy());
+        }
+        ValueKind::Number(Number::U64(0)) if text.starts_with('-') => return Err(lossy()),
+        ValueKind::Array(values) => {
+            for value in values {
+                check_numbers(source, value)?;
+            }
+        }
+        ValueKind::Map(map) => {
+            for member in map.iter() {
+                check_numbers(source, &member.value)?;
+            }
+        }
+        _ => (),
+    }
+
+    Ok(())
+}
+
+/// Check that no map in `value` has the same key more than once.
+fn check_duplicate_keys(source: &str, value: &Value) -> Result<(), ConvertError> {
+    match &value.kind {
+        ValueKind::Array(values) => {
+            for value in values {
+                check_duplicate_keys(source, value)?;
+            }
+        }
+        ValueKind::Map(map) => {
+            let mut keys = HashSet::new();
+
+            for member in map.iter() {
+                if !keys.insert(&member.key.name) {
+                    return Err(ConvertError::DuplicateKey {
+                        key: member.key.name.clone(),
+                        at: Cursor::at(source, member.key.span.start),
+                    });
+                }
+                check_duplicate_keys(source, &member.value)?;
+            }
+        }
+        _ => (),
+    }
+
+    Ok(())
+}
+
+/// Normalise a decimal number into its sign, significant digits, and
+/// exponent, so that numbers written differently can be compared exactly.
+fn decimal(text: &str) -> Option<(bool, String, i64)> {
+    let (negative, text) = match text.strip_prefix('-') {
+        Some(text) => (true, text),
+        None => (false, text),
+    };
+
+    let (mantissa, exponent) = match text.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, exponent.parse::<i64>().ok()?),
+        None => (text, 0),
+    };
+    let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+
+    let digits = format!("{int}{frac}");
+    let significant = digits.trim_end_matches('0');
+    let exponent = exponent - frac.len() as i64 + (digits.len() - significant.len()) as i64;
+    let significant = significant.trim_start_matches('0');
+
+    match significant.is_empty() {
+        // All zeros are the same number.
+        true => Some((false, String::new(), 0)),
+        false => Some((negative, significant.to_string(), exponent)),
+    }
+}
+
+/// Errors from converting documents.
+#[derive(Debug)]
+pub enum ConvertError {
+    /// The document has a syntax error or an invalid escape sequence.
+    Decode { message: String, at: Cursor },
+    /// A number would change if converted.
+    Number { text: String, at: Cursor },
+    /// A key is repeated in a map, which the configuration denies.
+    DuplicateKey { key: String, at: Cursor },
+    /// The converted document could not be written.
+    Write(String),
+}
+
+impl Display for ConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConvertError::Decode { message, at } => {
+                write!(f, "{}:{}: {message}", at.line, at.column)
+            }
+            ConvertError::Number { text, at } => write!(
+                f,
+                "{}:{}: number `{text}` cannot be converted without losing precision",
+                at.line, at.column
+            ),
+            ConvertError::DuplicateKey { key, at } => {
+                write!(f, "{}:{}: duplicate key `{key}`", at.line, at.column)
+            }
+            ConvertError::Write(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl Error for ConvertError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::linter::Linter;
+    use indoc::indoc;
+
+    const HJSON: &str = indoc! {"
+        # A comment.
+        name: my server
+        ports: [80, 443]
+        ratio: 1.50
+        description:
+          '''
+          two
+          lines
+          '''
+        limits: {}
+    "};
+
+    #[test]
+    fn hjson_to_json() {
+        assert_eq!(
+            to_json(HJSON, false).unwrap(),
+            concat!(
+                r#"{"name":"my server","ports":[80,443],"ratio":1.5,"#,
+                r#""description":"two\nlines","limits":{}}"#,
+                "\n"
+            )
+        );
+
+        assert_eq!(
+            to_json("a: [1e3, -0.25]", true).unwrap(),
+            indoc! {r#"
+                {
+                  "a": [
+                    1000.0,
+                    -0.25
+                  ]
+                }
+            "#}
+        );
+    }
+
+    #[test]
+    fn json_to_hjson() {
+        let json = to_json(HJSON, true).unwrap();
+
+        for config in [Config::default(), Config::strict()] {
+            let hjson = to_hjson(&json, &config).unwrap();
+            assert_eq!(
+                Linter::lint(&config, &hjson).unwrap(),
+                Vec::new(),
+                "{hjson}"
+            );
+            assert_eq!(to_json(&hjson, true).unwrap(), json);
+        }
+
+        assert_eq!(
+            to_hjson(r#"{"a b": ["c", "d e"], "f": 1.0}"#, &Config::default()).unwrap(),
+            indoc! {r#"
+                "a b": [
+                  c
+                  d e
+                ]
+                f: 1.0
+            "#}
+        );
+    }
+
+    #[test]
+    fn backslashes() {
+        // Strings ending in an escaped backslash, as in Windows paths.
+        let json = concat!(r#"{"C:\\":"C:\\","a\\\"b":"'c\\'","d":["\\"]}"#, "\n");
+
+        for config in [Config::default(), Config::strict()] {
+            let hjson = to_hjson(json, &config).unwrap();
+            assert_eq!(to_json(&hjson, false).unwrap(), json, "{hjson}");
+        }
+        assert_eq!(
+            to_json(r#"{"path": "C:\\", 'b': 'd\\'}"#, false).unwrap(),
+            concat!(r#"{"path":"C:\\","b":"d\\"}"#, "\n")
+        );
+    }
+
+    #[test]
+    fn numbers() {
+        // Precision which an `f64` can hold is fine, however it's written.
+        for number in [
+            "0.1",
+            "-2.50",
+            "1E3",
+            "12e-1",
+            "0.0",
+            "-0.0",
+            "123456789012345678",
+        ] {
+            let source = format!("a: {number}");
+            assert!(to_json(&source, false).is_ok(), "{number}");
+        }
+
+        let err = to_json("a: 1\nb: 12345678901234567890123", false).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "2:4: number `12345678901234567890123` cannot be converted without losing precision"
+        );
+        let err = to_json("a: -0", false).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "1:4: number `-0` cannot be converted without losing precision"
+        );
+
+        for number in ["0.10000000000000000001", "1e400", "-1e400", "-0"] {
+            let source = format!(r#"{{"a": {number}}}"#);
+            let err = to_hjson(&source, &Config::default()).unwrap_err();
+            assert!(matches!(err, ConvertError::Number { .. }), "{number}");
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let err = to_json("a: 1\nb: [1,, 2]", false).unwrap_err();
+        assert_eq!(err.to_string(), "2:7: expected value, got ,");
+
+        let source = r#"{"a": {"b": 1, "b": 2}}"#;
+        assert!(to_hjson(source, &Config::default()).is_ok());
+        let err = to_hjson(source, &Config::strict()).unwrap_err();
+        assert_eq!(err.to_string(), "1:16: duplicate key `b`");
+
+        // Lone surrogates can't be decoded into strings.
+        let err = to_hjson(r#"{"a": "\ud800"}"#, &Config::default()).unwrap_err();
+        assert_eq!(err.to_string(), "1:8: invalid escape sequence");
+    }
+}
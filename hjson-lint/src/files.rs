@@ -0,0 +1,352 @@
+//! Discovery of the files to lint from command-line arguments.
+//!
+//! Each argument may be a path to a file, a directory to search recursively, a
+//! glob pattern (e.g. `configs/**/*.hjson`), or `-` for standard input.
+//!
+//! Files named explicitly are always included. Files found by searching
+//! directories or expanding globs are filtered by their extension, and hidden
+//! files and directories (starting with `.`) are skipped while searching.
+//! Symbolic links to directories aren't followed while searching either.
+//! Globs only match hidden paths where they have a literal `.`, as in
+//! `.config/*.hjson`.
+
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+/// The extensions searched for by default.
+pub const DEFAULT_EXTENSIONS: &[&str] = &["hjson"];
+
+/// An input to be linted.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Input {
+    /// Standard input, given as `-`.
+    Stdin,
+    /// A file on disk.
+    File(PathBuf),
+}
+
+impl Display for Input {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Input::Stdin => f.write_str("<stdin>"),
+            Input::File(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+impl Input {
+    /// Read the whole input into a string.
+    pub fn read(&self) -> io::Result<String> {
+        match self {
+            Input::Stdin => io::read_to_string(io::stdin()),
+            Input::File(path) => fs::read_to_string(path),
+        }
+    }
+}
+
+/// Find all inputs described by the given arguments.
+///
+/// Inputs are returned in the order their arguments were given, with the
+/// contents of directories sorted by name. Each input is only returned once.
+pub fn find<S: AsRef<str>>(args: &[S], extensions: &[&str]) -> Result<Vec<Input>, FindError> {
+    let mut inputs = Inputs::default();
+
+    for arg in args {
+        let arg = arg.as_ref();
+
+        if arg == "-" {
+            inputs.push(Input::Stdin);
+            continue;
+        }
+
+        let path = Path::new(arg);
+        if path.is_dir() {
+            walk(path, extensions, &mut inputs)?;
+        } else if path.exists() {
+            inputs.push(Input::File(path.to_path_buf()));
+        } else if is_glob(arg) {
+            expand(arg, extensions, &mut inputs)?;
+        } else {
+            return Err(FindError::NotFound(arg.to_string()));
+        }
+    }
+
+    Ok(inputs.list)
+}
+
+/// Recursively search a directory for files with one of the `extensions`.
+///
+/// Symbolic links to directories aren't followed, so links back up the tree
+/// can't loop forever.
+fn walk(dir: &Path, extensions: &[&str], inputs: &mut Inputs) -> Result<(), FindError> {
+    let io_err = |err| FindError::Io(dir.to_path_buf(), err);
+
+    let mut entries = fs::read_dir(dir)
+        .map_err(io_err)?
+        .map(|entry| entry.and_then(|entry| Ok((entry.path(), entry.file_type()?))))
+        .collect::<io::Result<Vec<_>>>()
+        .map_err(io_err)?;
+    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    for (path, file_type) in entries {
+        if is_hidden(&path) || (file_type.is_symlink() && path.is_dir()) {
+            continue;
+        }
+
+        if file_type.is_dir() {
+            walk(&path, extensions, inputs)?;
+        } else if has_extension(&path, extensions) {
+            inputs.push(Input::File(path));
+        }
+    }
+
+    Ok(())
+}
+
+/// Expand a glob pattern, searching any directories it matches.
+///
+/// Hidden files and directories are only matched by parts of the pattern
+/// starting with a literal `.`. Patterns fail if they match no directories or
+/// files with one of the `extensions`.
+fn expand(pattern: &str, extensions: &[&str], inputs: &mut Inputs) -> Result<(), FindError> {
+    let paths = glob::glob(pattern)
+        .map_err(|err| FindError::Pattern(pattern.to_string(), err.msg.to_string()))?;
+
+    // `require_literal_leading_dot` also hides paths which the pattern names
+    // with a literal `.`, so hidden paths are skipped here instead.
+    let literal_dots: Vec<_> = Path::new(pattern)
+        .iter()
+        .filter_map(|part| part.to_str())
+        .filter(|part| part.starts_with('.'))
+        .filter_map(|part| glob::Pattern::new(part).ok())
+        .collect();
+    let visible = |path: &Path| {
+        path.components().all(|part| match part {
+            Component::Normal(name) => {
+                !is_hidden(Path::new(name))
+                    || literal_dots
+                        .iter()
+                        .any(|dot| name.to_str().is_some_and(|name| dot.matches(name)))
+            }
+            _ => true,
+        })
+    };
+
+    let mut matched = false;
+    for path in paths {
+        let path = path.map_err(|err| {
+            let path = err.path().to_path_buf();
+            FindError::Io(path, err.into())
+        })?;
+
+        if !visible(&path) {
+            continue;
+        }
+
+        if path.is_dir() {
+            walk(&path, extensions, inputs)?;
+            matched = true;
+        } else if has_extension(&path, extensions) {
+            inputs.push(Input::File(path));
+            matched = true;
+        }
+    }
+
+    match matched {
+        true => Ok(()),
+        false => Err(FindError::NoMatches(pattern.to_string())),
+    }
+}
+
+/// Inputs found so far, in the order they were found.
+#[derive(Default)]
+struct Inputs {
+    list: Vec<Input>,
+    seen: HashSet<Input>,
+}
+
+impl Inputs {
+    /// Add an input, unless it's already been found.
+    fn push(&mut self, input: Input) {
+        if self.seen.insert(input.clone()) {
+            self.list.push(input);
+        }
+    }
+}
+
+fn is_glob(arg: &str) -> bool {
+    arg.contains(['*', '?', '['])
+}
+
+fn is_hidden(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with('.'))
+}
+
+fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| extensions.contains(&ext))
+}
+
+/// Errors encountered while finding inputs.
+#[derive(Debug)]
+pub enum FindError {
+    /// A path that does not exist and is not a glob pattern.
+    NotFound(String),
+    /// A glob pattern which could not be parsed.
+    Pattern(String, String),
+    /// A glob pattern which matched no paths.
+    NoMatches(String),
+    /// An error reading a directory.
+    Io(PathBuf, io::Error),
+}
+
+impl Display for FindError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FindError::NotFound(path) => write!(f, "{path}: no such file or directory"),
+            FindError::Pattern(pattern, msg) => write!(f, "{pattern}: invalid pattern: {msg}"),
+            FindError::NoMatches(pattern) => write!(f, "{pattern}: no files matched pattern"),
+            FindError::Io(path, err) => write!(f, "{}: {err}", path.display()),
+        }
+    }
+}
+
+impl Error for FindError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// Create a fresh directory tree for testing with the given files, which
+    /// is removed when it's dropped.
+    fn tree(files: &[&str]) -> TempDir {
+        let dir = TempDir::new().unwrap();
+
+        for file in files {
+            let path = dir.path().join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+
+        dir
+    }
+
+    fn files(root: &Path, names: &[&str]) -> Vec<Input> {
+        names
+            .iter()
+            .map(|name| Input::File(root.join(name)))
+            .collect()
+    }
+
+    #[test]
+    fn directory() {
+        let dir = tree(&[
+            "b.hjson",
+            "a.hjson",
+            "c.json",
+            "sub/d.hjson",
+            ".hidden/e.hjson",
+        ]);
+        let root = dir.path();
+        let arg = root.to_str().unwrap();
+
+        assert_eq!(
+            find(&[arg], DEFAULT_EXTENSIONS).unwrap(),
+            files(root, &["a.hjson", "b.hjson", "sub/d.hjson"])
+        );
+        assert_eq!(
+            find(&[arg], &["hjson", "json"]).unwrap(),
+            files(root, &["a.hjson", "b.hjson", "c.json", "sub/d.hjson"])
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks() {
+        let dir = tree(&["a.hjson", "sub/b.hjson"]);
+        let root = dir.path();
+        std::os::unix::fs::symlink(root, root.join("sub/loop")).unwrap();
+        std::os::unix::fs::symlink(root.join("a.hjson"), root.join("sub/c.hjson")).unwrap();
+
+        // Links to files are followed, but links to directories aren't.
+        assert_eq!(
+            find(&[root.to_str().unwrap()], DEFAULT_EXTENSIONS).unwrap(),
+            files(root, &["a.hjson", "sub/b.hjson", "sub/c.hjson"])
+        );
+    }
+
+    #[test]
+    fn explicit_files() {
+        let dir = tree(&["a.json", "b.hjson"]);
+        let root = dir.path();
+        let a = root.join("a.json");
+        let b = root.join("b.hjson");
+        let args = [b.to_str().unwrap(), "-", a.to_str().unwrap(), "-"];
+
+        // Explicit files are not filtered by extension, and each is only listed once.
+        assert_eq!(
+            find(&args, DEFAULT_EXTENSIONS).unwrap(),
+            [Input::File(b), Input::Stdin, Input::File(a)]
+        );
+    }
+
+    #[test]
+    fn glob() {
+        let dir = tree(&["a/x.hjson", "a/y.json", "b/c/z.hjson"]);
+        let root = dir.path();
+        let pattern = format!("{}/**/*.hjson", root.display());
+
+        assert_eq!(
+            find(&[pattern], DEFAULT_EXTENSIONS).unwrap(),
+            files(root, &["a/x.hjson", "b/c/z.hjson"])
+        );
+
+        let pattern = format!("{}/*.nothing", root.display());
+        assert!(matches!(
+            find(&[pattern], DEFAULT_EXTENSIONS),
+            Err(FindError::NoMatches(_))
+        ));
+
+        // Matching only files with other extensions is the same as no matches.
+        let pattern = format!("{}/a/*.json", root.display());
+        assert!(matches!(
+            find(&[pattern], DEFAULT_EXTENSIONS),
+            Err(FindError::NoMatches(_))
+        ));
+    }
+
+    #[test]
+    fn glob_hidden() {
+        let dir = tree(&["a.hjson", ".b.hjson", ".c/d.hjson"]);
+        let root = dir.path();
+
+        // Like searching directories, wildcards don't match hidden paths.
+        let pattern = format!("{}/*", root.display());
+        assert_eq!(
+            find(&[pattern], DEFAULT_EXTENSIONS).unwrap(),
+            files(root, &["a.hjson"])
+        );
+
+        let pattern = format!("{}/.[!.]*", root.display());
+        assert_eq!(
+            find(&[pattern], DEFAULT_EXTENSIONS).unwrap(),
+            files(root, &[".b.hjson", ".c/d.hjson"])
+        );
+    }
+
+    #[test]
+    fn not_found() {
+        assert!(matches!(
+            find(&["does/not/exist.hjson"], DEFAULT_EXTENSIONS),
+            Err(FindError::NotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,417 @@
+//! Automatic fixes for lints.
+//!
+//! Lints can carry a [`Fix`], made of [`Edit`]s to the source which would
+//! resolve them. Fixes for a file are [applied](apply) together, skipping any
+//! whose edits overlap with those of an earlier fix. The changes can be
+//! previewed as a unified [diff].
+
+use crate::lexer::{TokenKind, Tokens};
+
+/// A change to the source: replacing `len` bytes at `offset` with `text`.
+///
+/// Insertions have a `len` of zero, while deletions have empty `text`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Edit {
+    pub offset: usize,
+    pub len: usize,
+    pub text: String,
+}
+
+impl Edit {
+    /// Whether this edit conflicts with another.
+    ///
+    /// Edits touching at their boundaries do not overlap, so inserting text
+    /// just before some deleted text is fine.
+    fn overlaps(&self, other: &Edit) -> bool {
+        self.offset < other.offset + other.len && other.offset < self.offset + self.len
+    }
+}
+
+/// A fix for a lint, made of edits that must all be applied together.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Fix {
+    pub edits: Vec<Edit>,
+}
+
+impl Fix {
+    /// A fix inserting `text` at `offset`.
+    pub fn insert(offset: usize, text: &str) -> Self {
+        Self::replace(offset, 0, text)
+    }
+
+    /// A fix deleting `len` bytes at `offset`.
+    pub fn delete(offset: usize, len: usize) -> Self {
+        Self::replace(offset, len, "")
+    }
+
+    /// A fix replacing `len` bytes at `offset` with `text`.
+    pub fn replace(offset: usize, len: usize, text: &str) -> Self {
+        Self {
+            edits: Vec::from([Edit {
+                offset,
+                len,
+                text: text.to_string(),
+            }]),
+        }
+    }
+}
+
+/// The result of applying fixes to some source.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Fixed {
+    /// The source with all applied fixes.
+    pub output: String,
+    /// Edits which were applied, in order of their offsets.
+    pub edits: Vec<Edit>,
+    /// The number of fixes which were applied.
+    pub applied: usize,
+    /// The number of fixes which were skipped because they overlapped.
+    pub skipped: usize,
+}
+
+/// Apply as many of the given fixes to `source` as possible.
+///
+/// Fixes are considered in order, and any fix with an edit overlapping one
+/// already accepted is skipped entirely. Re-linting the output will find any
+/// lints that remain.
+pub fn apply<'a>(source: &str, fixes: impl IntoIterator<Item = &'a Fix>) -> Fixed {
+    let mut edits: Vec<Edit> = Vec::new();
+    let mut applied = 0;
+    let mut skipped = 0;
+
+    for fix in fixes {
+        let overlaps = fix
+            .edits
+            .iter()
+            .any(|edit| edits.iter().any(|accepted| accepted.overlaps(edit)));
+
+        if overlaps {
+            skipped += 1;
+        } else {
+            edits.extend(fix.edits.iter().cloned());
+            applied += 1;
+        }
+    }
+
+    // Insertions go before any deletion at the same offset. Sorting is stable,
+    // so insertions at the same offset stay in order.
+    edits.sort_by_key(|edit| (edit.offset, edit.len));
+
+    Fixed {
+        output: splice(source, 0, source.len(), &edits),
+        edits,
+        applied,
+        skipped,
+    }
+}
+
+/// Apply sorted, non-overlapping `edits` to the section of `source` from
+/// `start` to `end`, which must contain all of them.
+fn splice(source: &str, start: usize, end: usize, edits: &[Edit]) -> String {
+    let mut output = String::new();
+    let mut offset = start;
+
+    for edit in edits {
+        output += &source[offset..edit.offset];
+        output += &edit.text;
+        offset = edit.offset + edit.len;
+    }
+    output += &source[offset..end];
+
+    output
+}
+
+/// Number of unchanged lines shown around changes in diffs.
+const CONTEXT: usize = 3;
+
+/// Render the changes made by sorted, non-overlapping `edits` as a unified
+/// diff of `file`.
+pub fn diff(file: &str, source: &str, edits: &[Edit]) -> String {
+    if edits.is_empty() {
+        return String::new();
+    }
+
+    // Byte offsets of the start of each line, plus the end of the source.
+    let mut line_starts: Vec<usize> = Vec::from([0]);
+    line_starts.extend(source.match_indices('\n').map(|(i, _)| i + 1));
+    if *line_starts.last().unwrap() != source.len() || line_starts.len() == 1 {
+        line_starts.push(source.len());
+    }
+    let line_count = line_starts.len() - 1;
+    let line_of = |offset: usize| line_starts.partition_point(|&start| start <= offset) - 1;
+
+    // Group edits into blocks of changed lines, merging edits which touch
+    // the same or adjacent lines.
+    let mut blocks: Vec<(usize, usize, Vec<Edit>)> = Vec::new();
+    for edit in edits {
+        let first = line_of(edit.offset).min(line_count.saturating_sub(1));
+        let last = line_of((edit.offset + edit.len).saturating_sub(1).max(edit.offset))
+            .clamp(first, line_count.saturating_sub(1));
+
+        match blocks.last_mut() {
+            Some((_, block_last, block_edits)) if first <= *block_last + 1 => {
+                *block_last = (*block_last).max(last);
+                block_edits.push(edit.clone());
+            }
+            _ => blocks.push((first, last, Vec::from([edit.clone()]))),
+        }
+    }
+
+    // Group blocks into hunks, merging blocks whose context would overlap.
+    let mut hunks: Vec<Vec<(usize, usize, Vec<Edit>)>> = Vec::new();
+    for block in blocks {
+        match hunks.last_mut() {
+            Some(hunk) if block.0 <= hunk.last().unwrap().1 + 2 * CONTEXT + 1 => hunk.push(block),
+            _ => hunks.push(Vec::from([block])),
+        }
+    }
+
+    let line = |i: usize| &source[line_starts[i]..line_starts[i + 1]];
+
+    let mut out = format!("--- {file}\n+++ {file}\n");
+    let mut shift: isize = 0;
+    for hunk in hunks {
+        let start = hunk.first().unwrap().0.saturating_sub(CONTEXT);
+        let end = (hunk.last().unwrap().1 + CONTEXT + 1).min(line_count);
+
+        let mut body = String::new();
+        let mut new_lines: isize = 0;
+        let mut at = start;
+        for (first, last, edits) in &hunk {
+            for i in at..*first {
+                push_line(&mut body, ' ', line(i));
+            }
+
+            for i in *first..=*last {
+                push_line(&mut body, '-', line(i));
+            }
+
+            let replaced = splice(source, line_starts[*first], line_starts[last + 1], edits);
+            for new in replaced.split_inclusive('\n') {
+                push_line(&mut body, '+', new);
+                new_lines += 1;
+            }
+
+            new_lines -= (last + 1 - first) as isize;
+            at = last + 1;
+        }
+        for i in at..end {
+            push_line(&mut body, ' ', line(i));
+        }
+
+        let old_len = end - start;
+        let new_len = (old_len as isize + new_lines) as usize;
+        let new_start = start as isize + shift;
+        out += &format!(
+            "@@ -{},{old_len} +{},{new_len} @@\n{body}",
+            start + 1,
+            new_start + 1
+        );
+        shift += new_lines;
+    }
+
+    out
+}
+
+/// Add a line to a diff, noting if it has no newline at the end.
+fn push_line(out: &mut String, prefix: char, line: &str) {
+    out.push(prefix);
+    out.push_str(line);
+    if !line.ends_with('\n') {
+        out.push_str("\n\\ No newline at end of file\n");
+    }
+}
+
+/// The text between the quotes of a single- or double-quoted string, if it
+/// has no escapes which would change meaning when unquoted.
+pub(crate) fn unescaped(quoted: &str) -> Option<&str> {
+    let inner = &quoted[1..quoted.len() - 1];
+    match inner.contains('\\') {
+        true => None,
+        false => Some(inner),
+    }
+}
+
+/// Whether the given tokens start with a single unquoted string covering the
+/// whole of `text`, so that it would be read back the same without quotes.
+pub(crate) fn lexes_unquoted(mut tokens: Tokens, text: &str) -> bool {
+    tokens
+        .next()
+        .is_some_and(|span| span.kind == TokenKind::TextUnquoted && span.len == text.len())
+}
+
+/// Quote text as a double-quoted string, escaping it as necessary.
+pub(crate) fn quote(text: &str) -> String {
+    let mut quoted = String::from("\"");
+
+    for c in text.chars() {
+        match c {
+            '"' => quoted += "\\\"",
+            '\\' => quoted += "\\\\",
+            '\t' => quoted += "\\t",
+            '\r' => quoted += "\\r",
+            '\n' => quoted += "\\n",
+            c if c.is_control() => quoted += &format!("\\u{:04x}", c as u32),
+            c => quoted.push(c),
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::linter::{AllowDeny, AllowDenyRequire, Config, Lint, Linter};
+    use indoc::indoc;
+
+    #[test]
+    fn apply_edits() {
+        let fixes = [
+            Fix::insert(6, ","),
+            Fix::delete(6, 2),
+            Fix::replace(0, 3, "\"foo\""),
+        ];
+        let fixed = apply("foo: 1  \nbar: 2", &fixes);
+
+        assert_eq!(fixed.output, "\"foo\": 1,\nbar: 2");
+        assert_eq!(fixed.applied, 3);
+        assert_eq!(fixed.skipped, 0);
+    }
+
+    #[test]
+    fn skip_overlapping() {
+        let braces = Fix {
+            edits: Vec::from([
+                Edit {
+                    offset: 0,
+                    len: 1,
+                    text: String::new(),
+                },
+                Edit {
+                    offset: 8,
+                    len: 1,
+                    text: String::new(),
+                },
+            ]),
+        };
+        let fixes = [
+            Fix::replace(2, 1, "'a'"),
+            braces,
+            Fix::delete(6, 1),
+            Fix::delete(7, 2),
+        ];
+        let fixed = apply("{ a: 1, }", &fixes);
+
+        // The last fix overlaps with the closing brace so it's skipped.
+        assert_eq!(fixed.output, " 'a': 1 ");
+        assert_eq!(fixed.applied, 3);
+        assert_eq!(fixed.skipped, 1);
+    }
+
+    #[test]
+    fn unified_diff() {
+        let source = indoc! {"
+            a: 1
+            b: 2
+            c: 3
+            d: 4
+            e: 5
+            f: 6
+            g: 7
+            h: 8
+            i: 9
+            j: 10
+            k: 11
+            l: 12"};
+        let fixes = [Fix::insert(4, ","), Fix::insert(source.len(), "\n}")];
+        let fixed = apply(source, &fixes);
+
+        assert_eq!(
+            diff("test.hjson", source, &fixed.edits),
+            indoc! {r"
+                --- test.hjson
+                +++ test.hjson
+                @@ -1,4 +1,4 @@
+                -a: 1
+                +a: 1,
+                 b: 2
+                 c: 3
+                 d: 4
+                @@ -9,4 +9,5 @@
+                 i: 9
+                 j: 10
+                 k: 11
+                -l: 12
+                \ No newline at end of file
+                +l: 12
+                +}
+                \ No newline at end of file
+            "}
+        );
+    }
+
+    #[test]
+    fn fix_lints() {
+        // Commas would become part of unquoted values, so aren't required.
+        let require = Config {
+            root_braces: AllowDenyRequire::Require,
+            missing_commas: AllowDeny::Allow,
+            trailing_commas: AllowDenyRequire::Allow,
+            unquoted_keys: AllowDenyRequire::Require,
+            unquoted_values: AllowDenyRequire::Require,
+            ..Config::strict()
+        };
+        let cases = [
+            (Config::strict(), "a: b c  \n'd': 1\n'e': 2,\n"),
+            (Config::strict(), "# comment\n{ a: [\n  1\n  2,\n] }"),
+            (require.clone(), "# comment"),
+            (require.clone(), "// comment\n# comment"),
+            (require, "'a': 'b'\n\"c\": {\n  d: 1\n}  \n"),
+        ];
+
+        for (config, source) in cases {
+            let lints = Linter::lint(&config, source).unwrap();
+            assert!(!lints.is_empty());
+
+            let fixed = apply(source, lints.iter().filter_map(Lint::fix));
+            assert_eq!(fixed.skipped, 0);
+            assert_eq!(Linter::lint(&config, &fixed.output).unwrap(), Vec::new());
+        }
+    }
+
+    #[test]
+    fn fix_backslash() {
+        let source = "{\n  \"path\": C:\\\n}\n";
+        let lints = Linter::lint(Config::strict(), source).unwrap();
+        let fixed = apply(source, lints.iter().filter_map(Lint::fix));
+        assert_eq!(fixed.output, "{\n  \"path\": \"C:\\\\\"\n}\n");
+
+        // Strings with escapes are never unquoted, so fixing again is a no-op.
+        let require = Config {
+            unquoted_values: AllowDenyRequire::Require,
+            ..Config::default()
+        };
+        assert_eq!(Linter::lint(&require, &fixed.output).unwrap(), Vec::new());
+    }
+
+    #[test]
+    fn unquoting() {
+        assert_eq!(unescaped("'a b'"), Some("a b"));
+        assert_eq!(unescaped(r#""a\"b""#), None);
+        assert_eq!(unescaped(r#""C:\\""#), None);
+
+        assert!(lexes_unquoted(Tokens::parse_value("a b"), "a b"));
+        assert!(!lexes_unquoted(Tokens::parse_value("1"), "1"));
+        assert!(!lexes_unquoted(Tokens::parse("a b"), "a b"));
+        assert!(!lexes_unquoted(Tokens::parse("// a"), "// a"));
+    }
+
+    #[test]
+    fn quoting() {
+        assert_eq!(quote("foo"), r#""foo""#);
+        assert_eq!(quote(r#"a"b\c"#), r#""a\"b\\c""#);
+        assert_eq!(quote("a\tb\u{1}"), r#""a\tb\u0001""#);
+    }
+}
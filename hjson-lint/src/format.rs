@@ -0,0 +1,725 @@
+//! Canonical formatting of Hjson documents.
+//!
+//! The formatter re-emits the format-preserving AST with one member per
+//! line, a consistent [`Style`] of indentation, commas, quotes and braces,
+//! and every comment kept next to the member it was written beside. Single
+//! blank lines between members are kept so that groups of related members
+//! stay grouped.
+//!
+//! Formatting is idempotent: formatting already-formatted source changes
+//! nothing, which is what `hjson-fmt --check` relies on.
+
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use crate::fix;
+use crate::lexer::{Span, TokenKind, Tokens};
+use crate::parser::ast::{Array, ArrayMember, Map, MapMember, Node, Value};
+use crate::parser::{ParseError, Parser};
+
+/// How a document should be formatted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Style {
+    /// Indentation for each level of nesting.
+    pub indent: Indent,
+    /// Where commas are placed after members.
+    pub commas: Commas,
+    /// How keys and string values are quoted.
+    pub quotes: Quotes,
+    /// Whether the whole document is surrounded by braces.
+    pub braces: Braces,
+}
+
+/// Indentation for each level of nesting.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Indent {
+    /// Some number of spaces.
+    Spaces(usize),
+    /// A single tab.
+    Tab,
+}
+
+impl Default for Indent {
+    fn default() -> Self {
+        Indent::Spaces(2)
+    }
+}
+
+/// Where commas are placed after members of maps and arrays.
+///
+/// Commas are never placed after unquoted strings, which would include them
+/// in the string.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Commas {
+    /// Between members, but not after the last, like JSON.
+    #[default]
+    Separate,
+    /// After every member, including the last.
+    Trailing,
+    /// Nowhere, relying on each member being on its own line.
+    Never,
+}
+
+/// How keys and string values are quoted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Quotes {
+    /// Leave quotes as they are.
+    #[default]
+    Preserve,
+    /// Quote every key and single-line string with double quotes, like JSON.
+    Double,
+    /// Remove quotes wherever the text would still parse the same.
+    Minimal,
+}
+
+/// Whether the whole document is surrounded by braces.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Braces {
+    /// Keep braces if the document has them.
+    #[default]
+    Preserve,
+    /// Always add braces around the document.
+    Always,
+    /// Remove braces from around the document.
+    Never,
+}
+
+/// Format Hjson `source` in the given style.
+pub fn format(source: &str, style: Style) -> Result<String, ParseError> {
+    let root = Parser::parse(source)?;
+
+    let mut formatter = Formatter {
+        source,
+        style,
+        out: String::new(),
+        last_end: 0,
+        fresh: true,
+    };
+    formatter.root(&root);
+
+    if !formatter.out.is_empty() {
+        formatter.out.push('\n');
+    }
+
+    Ok(formatter.out)
+}
+
+struct Formatter<'a> {
+    source: &'a str,
+    style: Style,
+    out: String,
+    /// Byte offset of the end of the last thing written from the source.
+    last_end: usize,
+    /// Whether nothing has been written since opening a map or array, where
+    /// blank lines are not kept.
+    fresh: bool,
+}
+
+impl<'a> Formatter<'a> {
+    fn root(&mut self, map: &Map) {
+        let braces = match self.style.braces {
+            Braces::Preserve => map.open_brace.inner.is_some(),
+            Braces::Always => true,
+            Braces::Never => false,
+        };
+
+        // Comments before the document, or before its first member if it
+        // has no braces.
+        for comment in comments(&map.open_brace.before) {
+            self.newline(0, comment.start.byte_offset);
+            self.comment(comment);
+        }
+
+        if braces {
+            let at = map.open_brace.inner.as_ref().map_or(0, offset);
+            self.newline(0, at);
+            self.map(map, 0);
+        } else {
+            for comment in comments(&map.open_brace.after) {
+                self.newline(0, comment.start.byte_offset);
+                self.comment(comment);
+            }
+            self.map_members(map, 0);
+        }
+
+        for comment in comments(&map.close_brace.after) {
+            self.newline(0, comment.start.byte_offset);
+            self.comment(comment);
+        }
+    }
+
+    /// Write a map, including its braces, with its members at `level + 1`.
+    fn map(&mut self, map: &Map, level: usize) {
+        self.out.push('{');
+        if let Some(brace) = &map.open_brace.inner {
+            self.last_end = end(brace);
+        }
+
+        let empty = map.members.is_empty()
+            && comments(&map.open_brace.after)
+                .chain(comments(&map.close_brace.before))
+                .next()
+                .is_none();
+        if empty {
+            self.out.push('}');
+            return;
+        }
+
+        self.trailing(comments(&map.open_brace.after).collect(), level + 1);
+        self.fresh = true;
+        self.map_members(map, level + 1);
+
+        self.newline(level, self.last_end);
+        self.out.push('}');
+        if let Some(brace) = &map.close_brace.inner {
+            self.last_end = end(brace);
+        }
+    }
+
+    /// Write the members of a map at `level`, along with any comments
+    /// between them.
+    fn map_members(&mut self, map: &Map, level: usize) {
+        // The parser attaches comments before a member to the end of the
+        // member before it.
+        let mut leading: Vec<&Span> = Vec::new();
+
+        for (i, member) in map.members.iter().enumerate() {
+            leading.extend(comments(&member.before));
+            let last = i == map.members.len() - 1;
+            self.map_member(member, leading, last, level);
+            leading = comments(&member.after).collect();
+        }
+
+        leading.extend(comments(&map.close_brace.before));
+        for comment in leading {
+            self.newline(level, comment.start.byte_offset);
+            self.comment(comment);
+        }
+    }
+
+    fn map_member<'m>(
+        &mut self,
+        member: &'m Node<MapMember>,
+        mut leading: Vec<&'m Span>,
+        last: bool,
+        level: usize,
+    ) {
+        let MapMember {
+            key,
+            colon,
+            value,
+            comma,
+        } = &member.inner;
+
+        let value_text = self.value_text(value, last);
+        let mut trailing: Vec<&'m Span> = comments(&colon.before)
+            .chain(comments(&colon.after))
+            .chain(comments(&comma.before))
+            .chain(comments(&comma.after))
+            .collect();
+
+        // Comments after an unquoted string would become part of it.
+        let unquoted = value_text.as_ref().is_some_and(|(_, unquoted)| *unquoted);
+        if unquoted {
+            leading.append(&mut trailing);
+        }
+
+        for comment in leading {
+            self.newline(level, comment.start.byte_offset);
+            self.comment(comment);
+        }
+
+        self.newline(level, key.start.byte_offset);
+        self.out += &self.key_text(key);
+        self.out += ": ";
+        self.value(value, value_text, level);
+        self.comma(comma, unquoted, last);
+        self.trailing(trailing, level);
+    }
+
+    /// Write an array, including its brackets, with its members at
+    /// `level + 1`.
+    fn array(&mut self, array: &Array, level: usize) {
+        self.out.push('[');
+        self.last_end = end(&array.open_bracket.inner);
+
+        let empty = array.members.is_empty()
+            && comments(&array.open_bracket.after)
+                .chain(comments(&array.close_bracket.before))
+                .next()
+                .is_none();
+        if empty {
+            self.out.push(']');
+            return;
+        }
+
+        self.trailing(comments(&array.open_bracket.after).collect(), level + 1);
+        self.fresh = true;
+
+        for (i, member) in array.members.iter().enumerate() {
+            let last = i == array.members.len() - 1;
+            self.array_member(member, last, level + 1);
+        }
+
+        for comment in comments(&array.close_bracket.before) {
+            self.newline(level + 1, comment.start.byte_offset);
+            self.comment(comment);
+        }
+
+        self.newline(level, self.last_end);
+        self.out.push(']');
+        self.last_end = end(&array.close_bracket.inner);
+    }
+
+    fn array_member(&mut self, member: &Node<ArrayMember>, last: bool, level: usize) {
+        let ArrayMember { value, comma } = &member.inner;
+
+        let value_text = self.value_text(value, last);
+        let mut leading: Vec<&Span> = comments(&member.before).collect();
+        let mut trailing: Vec<&Span> = comments(&comma.before)
+            .chain(comments(&comma.after))
+            .collect();
+
+        let unquoted = value_text.as_ref().is_some_and(|(_, unquoted)| *unquoted);
+        if unquoted {
+            leading.append(&mut trailing);
+        }
+
+        for comment in leading {
+            self.newline(level, comment.start.byte_offset);
+            self.comment(comment);
+        }
+
+        self.newline(level, value_offset(value));
+        self.value(value, value_text, level);
+        self.comma(comma, unquoted, last);
+        self.trailing(trailing, level);
+    }
+
+    /// Write a value, given the text for it if it's not a map or array.
+    fn value(&mut self, value: &Value, text: Option<(String, bool)>, level: usize) {
+        match (value, text) {
+            (Value::Map(map), _) => self.map(map, level),
+            (Value::Array(array), _) => self.array(array, level),
+            (Value::Value(span), Some(_)) if span.kind == TokenKind::TextMulti => {
+                self.out += &self.multi_line(span);
+                self.last_end = end(span);
+            }
+            (Value::Value(span), Some((text, _))) => {
+                self.out += &text;
+                self.last_end = end(span);
+            }
+            (Value::Value(span), None) => unreachable!("no text for {span:?}"),
+        }
+    }
+
+    /// Text to write for a value which is not a map or array, and whether
+    /// that text is an unquoted string.
+    fn value_text(&self, value: &Value, last: bool) -> Option<(String, bool)> {
+        let Value::Value(span) = value else {
+            return None;
+        };
+        let text = self.text(span);
+
+        let quoted = match span.kind {
+            TokenKind::TextUnquoted if self.style.quotes == Quotes::Double => fix::quote(text),
+            TokenKind::TextUnquoted => return Some((text.to_string(), true)),
+            TokenKind::TextSingle if self.style.quotes == Quotes::Double => double_quote(text),
+            TokenKind::TextSingle | TokenKind::TextDouble => text.to_string(),
+            _ => return Some((text.to_string(), false)),
+        };
+
+        // Only unquote strings which won't be followed by a comma, and which
+        // would be read back as the same string.
+        if self.style.quotes == Quotes::Minimal && !self.wants_comma(last) {
+            if let Some(inner) = fix::unescaped(text) {
+                if fix::lexes_unquoted(Tokens::parse_value(inner), inner) {
+                    return Some((inner.to_string(), true));
+                }
+            }
+        }
+
+        Some((quoted, false))
+    }
+
+    fn key_text(&self, key: &Span) -> String {
+        let text = self.text(key);
+
+        match (self.style.quotes, key.kind) {
+            (Quotes::Double, TokenKind::TextUnquoted) => fix::quote(text),
+            (Quotes::Double, TokenKind::TextSingle) => double_quote(text),
+            (Quotes::Minimal, TokenKind::TextSingle | TokenKind::TextDouble) => {
+                match fix::unescaped(text) {
+                    Some(inner) if fix::lexes_unquoted(Tokens::parse(inner), inner) => {
+                        inner.to_string()
+                    }
+                    _ => text.to_string(),
+                }
+            }
+            _ => text.to_string(),
+        }
+    }
+
+    /// Re-indent a multi-line string for the column it will be written at.
+    ///
+    /// Indentation up to the column of the opening `'''` is not part of the
+    /// string, so it has to move along with the string.
+    fn multi_line(&self, span: &Span) -> String {
+        let text = self.text(span);
+        let old_column = span.start.column - 1;
+        let new_column = self.out.len() - self.out.rfind('\n').map_or(0, |x| x + 1);
+
+        let mut lines = text.split('\n');
+        let mut out = String::from(lines.next().unwrap_or_default());
+        for line in lines {
+            out.push('\n');
+
+            let indent = line.len() - line.trim_start_matches([' ', '\t']).len();
+            let line = &line[indent.min(old_column)..];
+            if !line.is_empty() {
+                out += &" ".repeat(new_column);
+            }
+            out += line;
+        }
+
+        out
+    }
+
+    /// Write a comma if the style wants one here.
+    fn comma(&mut self, comma: &Node<Option<Span>>, unquoted: bool, last: bool) {
+        if !unquoted && self.wants_comma(last) {
+            self.out.push(',');
+        }
+        if let Some(comma) = &comma.inner {
+            self.last_end = self.last_end.max(end(comma));
+        }
+    }
+
+    fn wants_comma(&self, last: bool) -> bool {
+        match self.style.commas {
+            Commas::Separate => !last,
+            Commas::Trailing => true,
+            Commas::Never => false,
+        }
+    }
+
+    /// Write comments at the end of the current line.
+    ///
+    /// Block comments stay on the line, as does one line comment after them.
+    /// Any further line comments go on lines of their own.
+    fn trailing(&mut self, comments: Vec<&Span>, level: usize) {
+        let (block, line): (Vec<&Span>, Vec<&Span>) = comments
+            .into_iter()
+            .partition(|comment| comment.kind == TokenKind::BlockComment);
+
+        let mut line = line.into_iter();
+        for comment in block.into_iter().chain(line.next()) {
+            self.out.push(' ');
+            self.comment(comment);
+        }
+        for comment in line {
+            self.newline(level, self.last_end);
+            self.comment(comment);
+        }
+    }
+
+    fn comment(&mut self, comment: &Span) {
+        match comment.kind {
+            TokenKind::BlockComment => self.out += self.text(comment),
+            _ => self.out += self.text(comment).trim_end(),
+        }
+        self.last_end = self.last_end.max(end(comment));
+    }
+
+    /// Start a new line at `level`, for something found at `at` in the
+    /// source. A blank line is kept if there was one before it.
+    fn newline(&mut self, level: usize, at: usize) {
+        if !self.out.is_empty() {
+            self.out.push('\n');
+
+            let blank =
+                at > self.last_end && self.source[self.last_end..at].matches('\n').count() > 1;
+            if blank && !self.fresh {
+                self.out.push('\n');
+            }
+        }
+        self.fresh = false;
+
+        for _ in 0..level {
+            match self.style.indent {
+                Indent::Spaces(n) => self.out += &" ".repeat(n),
+                Indent::Tab => self.out.push('\t'),
+            }
+        }
+    }
+
+    fn text(&self, span: &Span) -> &'a str {
+        &self.source[span.start.byte_offset..end(span)]
+    }
+}
+
+impl FromStr for Indent {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "tab" => Ok(Indent::Tab),
+            _ => s
+                .parse()
+                .map(Indent::Spaces)
+                .map_err(|_| format!("invalid indent `{s}`, expected a number or `tab`")),
+        }
+    }
+}
+
+impl Display for Indent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Indent::Spaces(n) => write!(f, "{n}"),
+            Indent::Tab => f.write_str("tab"),
+        }
+    }
+}
+
+impl FromStr for Commas {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "separate" => Ok(Commas::Separate),
+            "trailing" => Ok(Commas::Trailing),
+            "never" => Ok(Commas::Never),
+            _ => Err(format!("unknown comma style `{s}`")),
+        }
+    }
+}
+
+impl FromStr for Quotes {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "preserve" => Ok(Quotes::Preserve),
+            "double" => Ok(Quotes::Double),
+            "minimal" => Ok(Quotes::Minimal),
+            _ => Err(format!("unknown quote style `{s}`")),
+        }
+    }
+}
+
+impl FromStr for Braces {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "preserve" => Ok(Braces::Preserve),
+            "always" => Ok(Braces::Always),
+            "never" => Ok(Braces::Never),
+            _ => Err(format!("unknown brace style `{s}`")),
+        }
+    }
+}
+
+fn comments(spans: &[Span]) -> impl Iterator<Item = &Span> {
+    spans.iter().filter(|span| {
+        matches!(
+            span.kind,
+            TokenKind::LineComment | TokenKind::HashComment | TokenKind::BlockComment
+        )
+    })
+}
+
+fn offset(span: &Span) -> usize {
+    span.start.byte_offset
+}
+
+fn end(span: &Span) -> usize {
+    span.start.byte_offset + span.len
+}
+
+/// Where a value starts in the source.
+fn value_offset(value: &Value) -> usize {
+    match value {
+        Value::Map(map) => map.open_brace.inner.as_ref().map_or(0, offset),
+        Value::Array(array) => offset(&array.open_bracket.inner),
+        Value::Value(span) => offset(span),
+    }
+}
+
+/// Convert a single-quoted string to a double-quoted one.
+fn double_quote(single: &str) -> String {
+    let mut out = String::from("\"");
+
+    let mut chars = single[1..single.len() - 1].chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('\'') => out.push('\''),
+                Some(c) => {
+                    out.push('\\');
+                    out.push(c);
+                }
+                None => out.push('\\'),
+            },
+            '"' => out += "\\\"",
+            c => out.push(c),
+        }
+    }
+
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::linter::{Config, Linter};
+    use indoc::indoc;
+
+    const SOURCE: &str = indoc! {r#"
+        // header
+        {
+          # server
+          'host': localhost
+          port: 8080, // the port
+          "tags": [ "a", 'b\'s', 3 ]
+
+
+          nested: { x: 1, /* inline */ y: null }
+          empty: {}
+          text:
+            '''
+            one
+              two
+            '''
+          // trailing
+        }
+    "#};
+
+    #[test]
+    fn default_style() {
+        let expected = indoc! {r#"
+            // header
+            {
+              # server
+              'host': localhost
+              port: 8080, // the port
+              "tags": [
+                "a",
+                'b\'s',
+                3
+              ],
+
+              nested: {
+                x: 1, /* inline */
+                y: null
+              },
+              empty: {},
+              text: '''
+                    one
+                      two
+                    '''
+              // trailing
+            }
+        "#};
+
+        assert_eq!(format(SOURCE, Style::default()).unwrap(), expected);
+    }
+
+    #[test]
+    fn json_style() {
+        let style = Style {
+            indent: Indent::Tab,
+            quotes: Quotes::Double,
+            braces: Braces::Always,
+            ..Style::default()
+        };
+        let source = indoc! {"
+            a: b c
+            'b': [ 1, 'x\\'y\"z' ]
+            c: {}
+        "};
+        let expected = indoc! {r#"
+            {
+            	"a": "b c",
+            	"b": [
+            		1,
+            		"x'y\"z"
+            	],
+            	"c": {}
+            }
+        "#};
+
+        let formatted = format(source, style).unwrap();
+        assert_eq!(formatted, expected);
+        assert_eq!(
+            Linter::lint(Config::strict(), &formatted).unwrap(),
+            Vec::new()
+        );
+    }
+
+    #[test]
+    fn minimal_style() {
+        let style = Style {
+            indent: Indent::Spaces(4),
+            commas: Commas::Never,
+            quotes: Quotes::Minimal,
+            braces: Braces::Never,
+        };
+        let source = indoc! {r#"
+            {
+              "a": "b c", // comment
+              "b c": 'true'
+              "d": [ "e", "f g", 'h\'' ]
+            }
+        "#};
+        let expected = indoc! {r#"
+            // comment
+            a: b c
+            "b c": 'true'
+            d: [
+                e
+                f g
+                'h\''
+            ]
+        "#};
+
+        assert_eq!(format(source, style).unwrap(), expected);
+    }
+
+    #[test]
+    fn trailing_commas() {
+        let style = Style {
+            commas: Commas::Trailing,
+            ..Style::default()
+        };
+        let source = "a: [1, 2]\nb: c\nd: 'e'\n";
+        let expected = "a: [\n  1,\n  2,\n],\nb: c\nd: 'e',\n";
+
+        assert_eq!(format(source, style).unwrap(), expected);
+    }
+
+    #[test]
+    fn idempotent() {
+        let styles = [
+            Style::default(),
+            Style {
+                indent: Indent::Tab,
+                commas: Commas::Trailing,
+                quotes: Quotes::Double,
+                braces: Braces::Never,
+            },
+            Style {
+                commas: Commas::Never,
+                quotes: Quotes::Minimal,
+                braces: Braces::Always,
+                ..Style::default()
+            },
+        ];
+
+        for style in styles {
+            let formatted = format(SOURCE, style).unwrap();
+            assert_eq!(format(&formatted, style).unwrap(), formatted);
+        }
+    }
+}
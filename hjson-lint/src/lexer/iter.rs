@@ -6,12 +6,14 @@ use super::number::Number;
 use super::symbol::Symbol;
 use super::text::Text;
 use super::whitespace::Whitespace;
-use super::{Parse, Token, TokenKind};
+use super::{Cursor, Parse, Token, TokenKind};
 
 pub struct Tokens<'a> {
     input: &'a str,
     cursor: Cursor,
     text_mode: TextMode,
+    /// Opening braces and brackets of the maps and arrays the cursor is in.
+    containers: Vec<TokenKind>,
     done: bool,
 }
 
@@ -21,9 +23,18 @@ impl<'a> Tokens<'a> {
             input,
             cursor: Cursor::default(),
             text_mode: TextMode::Key,
+            containers: Vec::new(),
             done: false,
         }
     }
+
+    /// Lex the input as if it appeared as a value, on the right of a `:`.
+    pub fn parse_value(input: &'a str) -> Self {
+        Self {
+            text_mode: TextMode::Value,
+            ..Self::parse(input)
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -52,8 +63,20 @@ impl<'a> Iterator for Tokens<'a> {
             }
         }
 
-        let token = next_token(self.input, self.text_mode)?;
+        let token = next_token(self.input, self.text_mode).unwrap_or_else(|| {
+            let len = self.input.find('\n').unwrap_or(self.input.len());
+            Token::new(TokenKind::Error, len)
+        });
+
+        match token.kind {
+            TokenKind::OpenBrace | TokenKind::OpenBracket => self.containers.push(token.kind),
+            TokenKind::CloseBrace | TokenKind::CloseBracket => {
+                self.containers.pop();
+            }
+            _ => {}
+        }
 
+        // Members of arrays are values, so are lexed like the values of maps.
         self.text_mode = match token.kind {
             TokenKind::Colon => TextMode::Value,
             TokenKind::Whitespace
@@ -61,6 +84,7 @@ impl<'a> Iterator for Tokens<'a> {
             | TokenKind::LineComment
             | TokenKind::HashComment
             | TokenKind::BlockComment => self.text_mode,
+            _ if self.containers.last() == Some(&TokenKind::OpenBracket) => TextMode::Value,
             _ => TextMode::Key,
         };
 
@@ -116,33 +140,6 @@ fn next_token(input: &str, text_mode: TextMode) -> Option<Token> {
     parsers.into_iter().find_map(|p| p(input))
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub struct Cursor {
-    pub line: usize,
-    pub column: usize,
-    pub byte_offset: usize,
-}
-
-impl Default for Cursor {
-    fn default() -> Self {
-        Cursor {
-            line: 1,
-            column: 1,
-            byte_offset: 0,
-        }
-    }
-}
-
-impl Cursor {
-    pub fn new(line: usize, column: usize, byte_offset: usize) -> Self {
-        Cursor {
-            line,
-            column,
-            byte_offset,
-        }
-    }
-}
-
 #[cfg(test)]
 mod test {
     use super::*;
@@ -234,4 +231,39 @@ mod test {
             assert_eq!(got, expected);
         }
     }
+
+    /// Test that members of arrays are lexed as values, not keys.
+    #[test]
+    fn array_values() {
+        let input = "a: [\n1\nb c\n{ d: 2 }, true\n]";
+
+        let tokens: Vec<_> = Tokens::parse(input).collect();
+        let expected = [
+            Span::new(TokenKind::TextUnquoted, Cursor::new(1, 1, 0), 1),
+            Span::new(TokenKind::Colon, Cursor::new(1, 2, 1), 1),
+            Span::new(TokenKind::Whitespace, Cursor::new(1, 3, 2), 1),
+            Span::new(TokenKind::OpenBracket, Cursor::new(1, 4, 3), 1),
+            Span::new(TokenKind::NewLine, Cursor::new(1, 5, 4), 1),
+            Span::new(TokenKind::Integer, Cursor::new(2, 1, 5), 1),
+            Span::new(TokenKind::NewLine, Cursor::new(2, 2, 6), 1),
+            Span::new(TokenKind::TextUnquoted, Cursor::new(3, 1, 7), 3),
+            Span::new(TokenKind::NewLine, Cursor::new(3, 4, 10), 1),
+            Span::new(TokenKind::OpenBrace, Cursor::new(4, 1, 11), 1),
+            Span::new(TokenKind::Whitespace, Cursor::new(4, 2, 12), 1),
+            Span::new(TokenKind::TextUnquoted, Cursor::new(4, 3, 13), 1),
+            Span::new(TokenKind::Colon, Cursor::new(4, 4, 14), 1),
+            Span::new(TokenKind::Whitespace, Cursor::new(4, 5, 15), 1),
+            Span::new(TokenKind::Integer, Cursor::new(4, 6, 16), 1),
+            Span::new(TokenKind::Whitespace, Cursor::new(4, 7, 17), 1),
+            Span::new(TokenKind::CloseBrace, Cursor::new(4, 8, 18), 1),
+            Span::new(TokenKind::Comma, Cursor::new(4, 9, 19), 1),
+            Span::new(TokenKind::Whitespace, Cursor::new(4, 10, 20), 1),
+            Span::new(TokenKind::Boolean, Cursor::new(4, 11, 21), 4),
+            Span::new(TokenKind::NewLine, Cursor::new(4, 15, 25), 1),
+            Span::new(TokenKind::CloseBracket, Cursor::new(5, 1, 26), 1),
+            Span::new(TokenKind::Eof, Cursor::new(5, 2, 27), 0),
+        ];
+
+        assert_eq!(tokens, expected);
+    }
 }
@@ -1,3 +1,4 @@
+use super::text::is_escaped;
 use super::TokenKind::{TextDouble, TextSingle, TextUnquoted};
 use super::{Parse, Token};
 
@@ -9,12 +10,12 @@ impl Parse for Key {
         if input.starts_with('\'') {
             let (idx, _) = input
                 .char_indices()
-                .find(|(i, c)| *i != 0 && *c == '\'' && !input[..*i].ends_with('\\'))?;
+                .find(|(i, c)| *i != 0 && *c == '\'' && !is_escaped(input, *i))?;
             Some(Token::new(TextSingle, idx + 1))
         } else if input.starts_with('"') {
             let (idx, _) = input
                 .char_indices()
-                .find(|(i, c)| *i != 0 && *c == '"' && !input[..*i].ends_with('\\'))?;
+                .find(|(i, c)| *i != 0 && *c == '"' && !is_escaped(input, *i))?;
             Some(Token::new(TextDouble, idx + 1))
         } else {
             let terminators = [',', ':', '[', ']', '{', '}'];
@@ -37,6 +38,7 @@ mod test {
         assert_eq!(Key::parse("'foo'"), Some(Token::new(TextSingle, 5)));
         assert_eq!(Key::parse("'a'b"), Some(Token::new(TextSingle, 3)));
         assert_eq!(Key::parse(r#"'a\'b'"#), Some(Token::new(TextSingle, 6)));
+        assert_eq!(Key::parse(r#"'a\\'"#), Some(Token::new(TextSingle, 5)));
     }
 
     #[test]
@@ -45,6 +47,7 @@ mod test {
         assert_eq!(Key::parse(r#""foo""#), Some(Token::new(TextDouble, 5)));
         assert_eq!(Key::parse(r#""a"b"#), Some(Token::new(TextDouble, 3)));
         assert_eq!(Key::parse(r#""a\"b""#), Some(Token::new(TextDouble, 6)));
+        assert_eq!(Key::parse(r#""a\\""#), Some(Token::new(TextDouble, 5)));
     }
 
     #[test]
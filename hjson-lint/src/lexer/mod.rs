@@ -12,7 +12,8 @@ use std::fmt::{self, Display};
 
 pub use boolean::Boolean;
 pub use comment::Comment;
-pub use iter::{Cursor, Span, Tokens};
+pub use hjson_parser::Cursor;
+pub use iter::{Span, Tokens};
 pub use key::Key;
 pub use null::Null;
 pub use number::Number;
@@ -61,6 +62,9 @@ pub enum TokenKind {
     TextUnquoted,
     NewLine,
     Whitespace,
+    /// Text which could not be lexed, such as an unterminated string, up to
+    /// the end of the line.
+    Error,
 }
 
 impl Display for TokenKind {
@@ -86,6 +90,7 @@ impl Display for TokenKind {
             Self::TextUnquoted => "unquoted string",
             Self::NewLine => "newline",
             Self::Whitespace => "whitespace",
+            Self::Error => "invalid token",
         };
         f.write_str(name)
     }
@@ -17,12 +17,12 @@ impl Parse for Text {
         } else if input.starts_with('\'') {
             let (idx, _) = input
                 .char_indices()
-                .find(|(i, c)| *i != 0 && *c == '\'' && !input[..*i].ends_with('\\'))?;
+                .find(|(i, c)| *i != 0 && *c == '\'' && !is_escaped(input, *i))?;
             Some(Token::new(TextSingle, idx + 1))
         } else if input.starts_with('"') {
             let (idx, _) = input
                 .char_indices()
-                .find(|(i, c)| *i != 0 && *c == '"' && !input[..*i].ends_with('\\'))?;
+                .find(|(i, c)| *i != 0 && *c == '"' && !is_escaped(input, *i))?;
             Some(Token::new(TextDouble, idx + 1))
         } else {
             let eol = input.find('\n').unwrap_or(input.len());
@@ -32,6 +32,12 @@ impl Parse for Text {
     }
 }
 
+/// Whether the character at byte `i` is escaped, by an odd number of
+/// backslashes before it.
+pub(super) fn is_escaped(input: &str, i: usize) -> bool {
+    input[..i].bytes().rev().take_while(|&b| b == b'\\').count() % 2 == 1
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -43,6 +49,8 @@ mod test {
         assert_eq!(Text::parse("'foo'"), Some(Token::new(TextSingle, 5)));
         assert_eq!(Text::parse("'a'b"), Some(Token::new(TextSingle, 3)));
         assert_eq!(Text::parse(r#"'a\'b'"#), Some(Token::new(TextSingle, 6)));
+        assert_eq!(Text::parse(r#"'a\\'"#), Some(Token::new(TextSingle, 5)));
+        assert_eq!(Text::parse(r#"'a\\\'b'"#), Some(Token::new(TextSingle, 8)));
     }
 
     #[test]
@@ -51,6 +59,8 @@ mod test {
         assert_eq!(Text::parse(r#""foo""#), Some(Token::new(TextDouble, 5)));
         assert_eq!(Text::parse(r#""a"b"#), Some(Token::new(TextDouble, 3)));
         assert_eq!(Text::parse(r#""a\"b""#), Some(Token::new(TextDouble, 6)));
+        assert_eq!(Text::parse(r#""a\\""#), Some(Token::new(TextDouble, 5)));
+        assert_eq!(Text::parse(r#""a\\\"b""#), Some(Token::new(TextDouble, 8)));
     }
 
     #[test]
@@ -1,16 +1,24 @@
 //! Hjson Linter
 //!
-//! This library contains modules for [lexing][lexer], [parsing][parser], and [linting][linter]
-//! [Hjson] documents.
+//! This library contains modules for [lexing][lexer], [parsing][parser], [linting][linter],
+//! [formatting][mod@format], and [converting][convert] [Hjson] documents, as well as for
+//! [finding files][files] to lint, [rendering][render] the resulting lints, and comparing them
+//! against a [baseline] of existing lints.
 //!
-//! Currently, the lexer and linter are tested but not against a large corpus of files. The
-//! parser has no automated testing except indirectly through the linter.
+//! Currently, the lexer, parser, and linter are tested but not against a large corpus of files.
 //!
 //! The parser generates a format-preserving AST (probably a concrete syntax tree actually)
-//! so that the linter can check whitespace, comments, etc.
+//! so that the linter can check whitespace, comments, etc. Syntax is checked by the
+//! error-resilient parser in [`hjson_parser`], so files with syntax errors can still be linted.
 //!
 //! [Hjson]: https://hjson.github.io/
 
+pub mod baseline;
+pub mod convert;
+pub mod files;
+pub mod fix;
+pub mod format;
 pub mod lexer;
 pub mod linter;
 pub mod parser;
+pub mod render;
@@ -0,0 +1,490 @@
+//! The rules built into the linter, each configured by a field of [`Config`].
+
+use std::collections::HashMap;
+
+use super::rule::{Level, Rule};
+use super::{Details, Label, Lint, LintKind, LintSpan, Linter};
+use crate::fix::{self, Edit, Fix};
+use crate::lexer::{Cursor, Span, TokenKind, Tokens};
+use crate::linter::config::{AllowDenyRequire, Config};
+use crate::parser::ast::{ArrayMember, Map, MapMember, Node};
+
+pub struct TrailingWhitespace;
+
+impl Rule for TrailingWhitespace {
+    fn name(&self) -> &'static str {
+        LintKind::TrailingWhitespace.name()
+    }
+
+    fn description(&self) -> &'static str {
+        "Whitespace at the end of a line."
+    }
+
+    fn default_level(&self) -> Level {
+        Level::Allow
+    }
+
+    fn level(&self, config: &Config) -> Level {
+        config.level(LintKind::TrailingWhitespace)
+    }
+
+    fn check_trivia(&self, linter: &mut Linter, trivia: &[Span]) {
+        // Span of the current run of whitespace we're looking at.
+        let mut whitespace = None;
+
+        // Scan tokens for whitespace followed by a newline.
+        for token in trivia {
+            match token.kind {
+                // Whitespace starts or extends the span.
+                TokenKind::Whitespace => {
+                    whitespace
+                        .get_or_insert(LintSpan {
+                            start: token.start,
+                            len: 0,
+                        })
+                        .len += token.len;
+                }
+                // New lines and EOLs publish a lint and reset the span.
+                TokenKind::NewLine | TokenKind::Eof => {
+                    if let Some(span) = whitespace {
+                        let fix = Fix::delete(span.start.byte_offset, span.len);
+                        linter.report(Lint {
+                            kind: LintKind::TrailingWhitespace,
+                            level: Level::Deny,
+                            span,
+                            fix: Some(fix),
+                            details: Details::default(),
+                        });
+                    }
+                    whitespace = None
+                }
+                // Anything else (comments) resets the span.
+                _ => whitespace = None,
+            }
+        }
+    }
+}
+
+pub struct ImplicitBraces;
+
+impl Rule for ImplicitBraces {
+    fn name(&self) -> &'static str {
+        LintKind::ImplicitBraces.name()
+    }
+
+    fn description(&self) -> &'static str {
+        "Braces around the root map, or their absence if they're required."
+    }
+
+    fn default_level(&self) -> Level {
+        Level::Allow
+    }
+
+    fn level(&self, config: &Config) -> Level {
+        config.level(LintKind::ImplicitBraces)
+    }
+
+    fn check_root(&self, linter: &mut Linter, map: &Map) {
+        match linter.config.root_braces {
+            AllowDenyRequire::Deny => {
+                if let Some(ref brace) = map.open_brace.inner {
+                    // Remove both the opening and closing brace.
+                    let fix = Fix {
+                        edits: [Some(brace), map.close_brace.inner.as_ref()]
+                            .into_iter()
+                            .flatten()
+                            .map(|brace| Edit {
+                                offset: brace.start.byte_offset,
+                                len: brace.len,
+                                text: String::new(),
+                            })
+                            .collect(),
+                    };
+
+                    let labels = map
+                        .close_brace
+                        .inner
+                        .as_ref()
+                        .map(|close| Label::new("closing brace here", close.start, close.len));
+
+                    linter.report(Lint {
+                        kind: LintKind::ImplicitBraces,
+                        level: Level::Deny,
+                        span: LintSpan {
+                            start: brace.start,
+                            len: brace.len,
+                        },
+                        fix: Some(fix),
+                        details: Details {
+                            labels: labels.into_iter().collect(),
+                            ..Details::default()
+                        },
+                    });
+                }
+            }
+            AllowDenyRequire::Require if map.open_brace.inner.is_none() => {
+                let cursor = map
+                    .open_brace
+                    .before
+                    .last()
+                    .map_or(Cursor::default(), |span| {
+                        let newline = span.kind == TokenKind::NewLine;
+                        Cursor {
+                            line: span.start.line + if newline { 1 } else { 0 },
+                            column: if newline {
+                                1
+                            } else {
+                                span.start.column + span.len
+                            },
+                            byte_offset: span.start.byte_offset + span.len,
+                        }
+                    });
+
+                // Wrap everything after any leading comments in braces. A line
+                // comment only ends the leading trivia when nothing follows
+                // it, so the brace needs a line of its own.
+                let line_comment = map.open_brace.before.last().is_some_and(|span| {
+                    matches!(span.kind, TokenKind::LineComment | TokenKind::HashComment)
+                });
+                let open = match line_comment {
+                    true => "\n{\n",
+                    false => "{\n",
+                };
+                let close = match linter.input.ends_with('\n') {
+                    true => "}\n",
+                    false => "\n}",
+                };
+                let fix = Fix {
+                    edits: Vec::from([
+                        Edit {
+                            offset: cursor.byte_offset,
+                            len: 0,
+                            text: String::from(open),
+                        },
+                        Edit {
+                            offset: linter.input.len(),
+                            len: 0,
+                            text: String::from(close),
+                        },
+                    ]),
+                };
+
+                linter.report(Lint {
+                    kind: LintKind::ImplicitBraces,
+                    level: Level::Deny,
+                    span: LintSpan {
+                        start: cursor,
+                        len: 0,
+                    },
+                    fix: Some(fix),
+                    details: Details::default(),
+                });
+            }
+            _ => (),
+        }
+    }
+}
+
+pub struct DuplicateKey;
+
+impl Rule for DuplicateKey {
+    fn name(&self) -> &'static str {
+        LintKind::DuplicateKey.name()
+    }
+
+    fn description(&self) -> &'static str {
+        "The same key appearing more than once in a map."
+    }
+
+    fn default_level(&self) -> Level {
+        Level::Allow
+    }
+
+    fn level(&self, config: &Config) -> Level {
+        config.level(LintKind::DuplicateKey)
+    }
+
+    fn check_map(&self, linter: &mut Linter, map: &Map) {
+        // Keys are compared as decoded, so `a`, `'a'`, and `"a"` are the same.
+        let mut first_keys = HashMap::new();
+
+        for member in &map.members {
+            let key = &member.inner.key;
+            let text = linter.text(key);
+            let name = hjson_parser::value::unquote(text).unwrap_or_else(|_| text.to_string());
+
+            let Some(first) = first_keys.get(&name) else {
+                first_keys.insert(name, key);
+                continue;
+            };
+
+            linter.report(Lint {
+                kind: LintKind::DuplicateKey,
+                level: Level::Deny,
+                span: LintSpan {
+                    start: key.start,
+                    len: key.len,
+                },
+                fix: None,
+                details: Details {
+                    labels: Vec::from([Label::new("first defined here", first.start, first.len)]),
+                    notes: Vec::from([String::from(
+                        "parsers disagree on which value to use for a repeated key",
+                    )]),
+                    help: Some(String::from("remove or rename one of the keys")),
+                },
+            });
+        }
+    }
+}
+
+pub struct TrailingComma;
+
+impl Rule for TrailingComma {
+    fn name(&self) -> &'static str {
+        LintKind::TrailingComma.name()
+    }
+
+    fn description(&self) -> &'static str {
+        "A comma after the last member of a map or array, or its absence if it's required."
+    }
+
+    fn default_level(&self) -> Level {
+        Level::Allow
+    }
+
+    fn level(&self, config: &Config) -> Level {
+        config.level(LintKind::TrailingComma)
+    }
+
+    fn check_map_member(&self, linter: &mut Linter, member: &Node<MapMember>, last: bool) {
+        if last {
+            self.check_comma(linter, &member.inner.comma);
+        }
+    }
+
+    fn check_array_member(&self, linter: &mut Linter, member: &Node<ArrayMember>, last: bool) {
+        if last {
+            self.check_comma(linter, &member.inner.comma);
+        }
+    }
+}
+
+impl TrailingComma {
+    fn check_comma(&self, linter: &mut Linter, comma: &Node<Option<Span>>) {
+        // If this comma site isn't followed by a new line, we don't treat it as trailing.
+        if !comma
+            .after
+            .iter()
+            .any(|span| span.kind == TokenKind::NewLine || span.kind == TokenKind::Eof)
+        {
+            return;
+        };
+
+        // Check for trailing commas.
+        match linter.config.trailing_commas {
+            AllowDenyRequire::Deny => {
+                if let Some(ref node) = comma.inner {
+                    linter.report(Lint {
+                        kind: LintKind::TrailingComma,
+                        level: Level::Deny,
+                        span: LintSpan {
+                            start: node.start,
+                            len: node.len,
+                        },
+                        fix: Some(Fix::delete(node.start.byte_offset, node.len)),
+                        details: Details::default(),
+                    })
+                }
+            }
+            AllowDenyRequire::Require if comma.inner.is_none() => {
+                // There's always a new line after the comma site by now.
+                let Some(first) = comma.before.iter().chain(&comma.after).next() else {
+                    return;
+                };
+                let start = first.start;
+                linter.report(Lint {
+                    kind: LintKind::TrailingComma,
+                    level: Level::Deny,
+                    span: LintSpan { start, len: 0 },
+                    fix: Some(Fix::insert(start.byte_offset, ",")),
+                    details: Details::default(),
+                })
+            }
+            _ => (),
+        }
+    }
+}
+
+pub struct MissingComma;
+
+impl Rule for MissingComma {
+    fn name(&self) -> &'static str {
+        LintKind::MissingComma.name()
+    }
+
+    fn description(&self) -> &'static str {
+        "Members separated by a new line rather than a comma."
+    }
+
+    fn default_level(&self) -> Level {
+        Level::Allow
+    }
+
+    fn level(&self, config: &Config) -> Level {
+        config.level(LintKind::MissingComma)
+    }
+
+    fn check_map_member(&self, linter: &mut Linter, member: &Node<MapMember>, last: bool) {
+        if !last {
+            self.check_comma(linter, &member.inner.comma);
+        }
+    }
+
+    fn check_array_member(&self, linter: &mut Linter, member: &Node<ArrayMember>, last: bool) {
+        if !last {
+            self.check_comma(linter, &member.inner.comma);
+        }
+    }
+}
+
+impl MissingComma {
+    fn check_comma(&self, linter: &mut Linter, comma: &Node<Option<Span>>) {
+        if comma.inner.is_some() {
+            return;
+        }
+
+        // Members with nothing at all between them, which can only come from
+        // files with syntax errors, aren't separated by a new line.
+        let Some(first) = comma.before.iter().chain(&comma.after).next() else {
+            return;
+        };
+
+        linter.report(Lint {
+            kind: LintKind::MissingComma,
+            level: Level::Deny,
+            span: LintSpan {
+                start: first.start,
+                len: 0,
+            },
+            fix: Some(Fix::insert(first.start.byte_offset, ",")),
+            details: Details::default(),
+        })
+    }
+}
+
+pub struct UnquotedKey;
+
+impl Rule for UnquotedKey {
+    fn name(&self) -> &'static str {
+        LintKind::UnquotedKey.name()
+    }
+
+    fn description(&self) -> &'static str {
+        "Map keys written without quotes, or with quotes they don't need if that's required."
+    }
+
+    fn default_level(&self) -> Level {
+        Level::Allow
+    }
+
+    fn level(&self, config: &Config) -> Level {
+        config.level(LintKind::UnquotedKey)
+    }
+
+    fn check_map_member(&self, linter: &mut Linter, member: &Node<MapMember>, _last: bool) {
+        let key = &member.inner.key;
+        let replacement = match linter.config.unquoted_keys {
+            AllowDenyRequire::Allow => None,
+            AllowDenyRequire::Deny => {
+                (key.kind == TokenKind::TextUnquoted).then(|| fix::quote(linter.text(key)))
+            }
+            AllowDenyRequire::Require => quoted_text(linter, key)
+                .filter(|text| fix::lexes_unquoted(Tokens::parse(text), text))
+                .map(String::from),
+        };
+
+        if let Some(replacement) = replacement {
+            linter.report(Lint {
+                kind: LintKind::UnquotedKey,
+                level: Level::Deny,
+                span: LintSpan {
+                    start: key.start,
+                    len: key.len,
+                },
+                fix: Some(Fix::replace(key.start.byte_offset, key.len, &replacement)),
+                details: Details::default(),
+            });
+        }
+    }
+}
+
+pub struct UnquotedValue;
+
+impl Rule for UnquotedValue {
+    fn name(&self) -> &'static str {
+        LintKind::UnquotedValue.name()
+    }
+
+    fn description(&self) -> &'static str {
+        "String values written without quotes, or with quotes they don't need if that's required."
+    }
+
+    fn default_level(&self) -> Level {
+        Level::Allow
+    }
+
+    fn level(&self, config: &Config) -> Level {
+        config.level(LintKind::UnquotedValue)
+    }
+
+    fn check_value(&self, linter: &mut Linter, value: &Span, comma: &Node<Option<Span>>) {
+        let replacement = match linter.config.unquoted_values {
+            AllowDenyRequire::Allow => None,
+            AllowDenyRequire::Deny => {
+                (value.kind == TokenKind::TextUnquoted).then(|| fix::quote(linter.text(value)))
+            }
+            AllowDenyRequire::Require => {
+                // Unquoted values run to the end of the line, so they would
+                // swallow any comma, comment, or closing bracket after them.
+                let ends_line = comma.inner.is_none()
+                    && comma.after.split_last().is_some_and(|(last, rest)| {
+                        matches!(last.kind, TokenKind::NewLine | TokenKind::Eof)
+                            && rest.iter().all(|span| span.kind == TokenKind::Whitespace)
+                    });
+
+                quoted_text(linter, value)
+                    .filter(|text| {
+                        ends_line && fix::lexes_unquoted(Tokens::parse_value(text), text)
+                    })
+                    .map(String::from)
+            }
+        };
+
+        if let Some(replacement) = replacement {
+            linter.report(Lint {
+                kind: LintKind::UnquotedValue,
+                level: Level::Deny,
+                span: LintSpan {
+                    start: value.start,
+                    len: value.len,
+                },
+                fix: Some(Fix::replace(
+                    value.start.byte_offset,
+                    value.len,
+                    &replacement,
+                )),
+                details: Details::default(),
+            });
+        }
+    }
+}
+
+/// Text between the quotes of a single- or double-quoted string, if it
+/// doesn't contain any escapes which would change meaning when unquoted.
+fn quoted_text<'a>(linter: &Linter<'a>, span: &Span) -> Option<&'a str> {
+    match span.kind {
+        TokenKind::TextSingle | TokenKind::TextDouble => fix::unescaped(linter.text(span)),
+        _ => None,
+    }
+}
@@ -1,5 +1,85 @@
+//! Configuration of the linter's rules.
+//!
+//! Configuration can be built in code, or loaded from a [`FILE_NAME`] file
+//! written in Hjson. The file maps the name of each field of [`Config`], or
+//! the kebab-case name of its rule, to `allow`, `deny`, or (where supported)
+//! `require`. Rules not mentioned are allowed unless the file `extends` one of
+//! the [`PRESETS`]:
+//!
+//! ```hjson
+//! {
+//!   extends: strict
+//!   trailing_commas: require
+//!   unquoted_keys: allow
+//!   levels: {
+//!     missing-comma: warn
+//!   }
+//! }
+//! ```
+//!
+//! Rules which aren't allowed are denied, unless `levels` gives them another
+//! [`Level`] by their kebab-case names.
+//!
+//! Rules can also be changed for parts of each document by [`Scope`]s, keyed
+//! by patterns of the paths to them:
+//!
+//! ```hjson
+//! {
+//!   scopes: {
+//!     "/registers/*/fields/*/desc": { unquoted_values: "allow" }
+//!   }
+//! }
+//! ```
+//!
+//! A [`ConfigFile`] may also change rules for some paths with `overrides`,
+//! where later sections take precedence, and skip paths with `ignore`:
+//!
+//! ```hjson
+//! {
+//!   overrides: {
+//!     "generated/**": { unquoted_keys: "allow" }
+//!   }
+//!   ignore: ["vendor", "*.min.hjson"]
+//! }
+//! ```
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use glob::{MatchOptions, Pattern};
+use hjson_parser::ser::{Commas, Options};
+
+use super::scope::Scope;
+use super::{Level, LintKind};
+use crate::lexer::{Cursor, Span, TokenKind};
+use crate::parser::ast::{MapMember, Value};
+use crate::parser::{ParseError, Parser};
+
+/// Name of the configuration file, which applies to all files in the
+/// directory it's in and to those in its subdirectories.
+pub const FILE_NAME: &str = ".hjson-lint.hjson";
+
+/// Names of the configuration presets, which can be loaded with
+/// [`Config::preset`].
+pub const PRESETS: &[&str] = &[
+    "default",
+    "strict",
+    "jsonc",
+    "hjson-canonical",
+    "relaxed-hjson",
+];
+
+/// [`PRESETS`] as a list for error messages.
+const PRESET_NAMES: &str = "`default`, `strict`, `jsonc`, `hjson-canonical`, or `relaxed-hjson`";
+
 /// Configuration for which linting rules to apply.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Config {
     /// Whether to allow or deny trailing whitespace at the ends of lines.
     pub trailing_whitespace: AllowDeny,
@@ -22,6 +102,35 @@ pub struct Config {
     /// Whether to allow, deny, or require (where permitted) that map keys
     /// are unquoted.
     pub unquoted_keys: AllowDenyRequire,
+
+    /// Whether to allow or deny the same key appearing more than once in a
+    /// map.
+    pub duplicate_keys: AllowDeny,
+
+    /// Whether to allow or deny suppression comments which don't suppress
+    /// any lints.
+    pub unused_suppressions: AllowDeny,
+
+    /// Levels of rules by name, for those which aren't allowed above. Rules
+    /// without a level are denied, and those from a
+    /// [`Registry`](super::Registry) have their default level.
+    pub levels: BTreeMap<String, Level>,
+
+    /// Scopes which change the rules for parts of each document, in the
+    /// order they apply.
+    pub scopes: Vec<Scope>,
+}
+
+impl From<Config> for Cow<'_, Config> {
+    fn from(config: Config) -> Self {
+        Cow::Owned(config)
+    }
+}
+
+impl<'a> From<&'a Config> for Cow<'a, Config> {
+    fn from(config: &'a Config) -> Self {
+        Cow::Borrowed(config)
+    }
 }
 
 impl Default for Config {
@@ -34,6 +143,10 @@ impl Default for Config {
             trailing_commas: AllowDenyRequire::Allow,
             unquoted_values: AllowDenyRequire::Allow,
             unquoted_keys: AllowDenyRequire::Allow,
+            duplicate_keys: AllowDeny::Allow,
+            unused_suppressions: AllowDeny::Allow,
+            levels: BTreeMap::new(),
+            scopes: Vec::new(),
         }
     }
 }
@@ -48,10 +161,616 @@ impl Config {
             trailing_commas: AllowDenyRequire::Deny,
             unquoted_values: AllowDenyRequire::Deny,
             unquoted_keys: AllowDenyRequire::Deny,
+            duplicate_keys: AllowDeny::Deny,
+            unused_suppressions: AllowDeny::Deny,
+            levels: BTreeMap::new(),
+            scopes: Vec::new(),
+        }
+    }
+
+    /// JSON with comments, as used by editors' settings files, which also
+    /// allows trailing commas. Comments aren't linted, so [`Config::strict`]
+    /// allows them too.
+    pub fn jsonc() -> Self {
+        Self {
+            trailing_commas: AllowDenyRequire::Allow,
+            ..Self::strict()
+        }
+    }
+
+    /// Hjson as written by the reference implementation's stringifier, with
+    /// braces around the document, no commas, and keys and strings only
+    /// quoted where they need to be.
+    pub fn hjson_canonical() -> Self {
+        Self {
+            trailing_whitespace: AllowDeny::Deny,
+            root_braces: AllowDenyRequire::Require,
+            missing_commas: AllowDeny::Allow,
+            trailing_commas: AllowDenyRequire::Deny,
+            unquoted_values: AllowDenyRequire::Require,
+            unquoted_keys: AllowDenyRequire::Require,
+            duplicate_keys: AllowDeny::Deny,
+            unused_suppressions: AllowDeny::Deny,
+            levels: BTreeMap::new(),
+            scopes: Vec::new(),
         }
     }
+
+    /// Any Hjson style, denying only duplicate keys and unused suppression
+    /// comments, which are likely to be mistakes.
+    pub fn relaxed_hjson() -> Self {
+        Self {
+            duplicate_keys: AllowDeny::Deny,
+            unused_suppressions: AllowDeny::Deny,
+            ..Self::default()
+        }
+    }
+
+    /// Level the given kind of lint is reported at.
+    pub fn level(&self, kind: LintKind) -> Level {
+        let enabled = match kind {
+            LintKind::TrailingWhitespace => self.trailing_whitespace.into(),
+            LintKind::ImplicitBraces => self.root_braces.into(),
+            LintKind::MissingComma => self.missing_commas.into(),
+            LintKind::TrailingComma => self.trailing_commas.into(),
+            LintKind::UnquotedValue => self.unquoted_values.into(),
+            LintKind::UnquotedKey => self.unquoted_keys.into(),
+            LintKind::DuplicateKey => self.duplicate_keys.into(),
+            LintKind::UnusedSuppression => self.unused_suppressions.into(),
+            LintKind::Custom { .. } => Level::Deny,
+        };
+
+        match enabled {
+            Level::Allow => Level::Allow,
+            _ => self.levels.get(kind.name()).copied().unwrap_or(Level::Deny),
+        }
+    }
+
+    /// Set the level of the rule with the given name, unless it's already
+    /// forbidden.
+    ///
+    /// Allowing a built-in rule sets it to `allow` above, and giving an
+    /// allowed rule any other level sets it to `deny`.
+    pub fn set_level(&mut self, rule: &str, level: Level) {
+        if self.forbids(rule) {
+            return;
+        }
+
+        let on = level != Level::Allow;
+        if let Some(kind) = LintKind::from_name(rule) {
+            if let Some(state) = self.allow_deny_mut(kind) {
+                *state = state.enabled(on);
+            } else if let Some(state) = self.allow_deny_require_mut(kind) {
+                *state = state.enabled(on);
+            }
+        }
+
+        self.levels.insert(rule.to_string(), level);
+    }
+
+    /// Whether the rule with the given name is forbidden.
+    fn forbids(&self, rule: &str) -> bool {
+        self.levels.get(rule) == Some(&Level::Forbid)
+    }
+
+    /// The field configuring a kind of lint, if it can't be required.
+    fn allow_deny_mut(&mut self, kind: LintKind) -> Option<&mut AllowDeny> {
+        match kind {
+            LintKind::TrailingWhitespace => Some(&mut self.trailing_whitespace),
+            LintKind::MissingComma => Some(&mut self.missing_commas),
+            LintKind::DuplicateKey => Some(&mut self.duplicate_keys),
+            LintKind::UnusedSuppression => Some(&mut self.unused_suppressions),
+            _ => None,
+        }
+    }
+
+    /// The field configuring a kind of lint, if it can be required.
+    fn allow_deny_require_mut(&mut self, kind: LintKind) -> Option<&mut AllowDenyRequire> {
+        match kind {
+            LintKind::ImplicitBraces => Some(&mut self.root_braces),
+            LintKind::TrailingComma => Some(&mut self.trailing_commas),
+            LintKind::UnquotedValue => Some(&mut self.unquoted_values),
+            LintKind::UnquotedKey => Some(&mut self.unquoted_keys),
+            _ => None,
+        }
+    }
+
+    /// Configuration with the given preset name, if one exists.
+    ///
+    /// The presets are listed in [`PRESETS`]: `default`, which allows
+    /// everything, [`strict`](Self::strict), [`jsonc`](Self::jsonc),
+    /// [`hjson-canonical`](Self::hjson_canonical), and
+    /// [`relaxed-hjson`](Self::relaxed_hjson).
+    pub fn preset(name: &str) -> Option<Self> {
+        match name {
+            "default" => Some(Self::default()),
+            "strict" => Some(Self::strict()),
+            "jsonc" => Some(Self::jsonc()),
+            "hjson-canonical" => Some(Self::hjson_canonical()),
+            "relaxed-hjson" => Some(Self::relaxed_hjson()),
+            _ => None,
+        }
+    }
+
+    /// Options for serializing documents which pass this configuration.
+    ///
+    /// Anything allowed is written as idiomatic Hjson.
+    pub fn serializer_options(&self) -> Options {
+        let commas = match (self.trailing_commas, self.missing_commas) {
+            (AllowDenyRequire::Require, _) => Commas::Trailing,
+            (_, AllowDeny::Deny) => Commas::Separate,
+            _ => Commas::Never,
+        };
+
+        Options {
+            root_braces: self.root_braces == AllowDenyRequire::Require,
+            unquoted_keys: self.unquoted_keys != AllowDenyRequire::Deny,
+            unquoted_values: self.unquoted_values != AllowDenyRequire::Deny,
+            commas,
+            multi_line_strings: self.unquoted_values != AllowDenyRequire::Deny,
+            ..Options::default()
+        }
+    }
+
+    /// Parse the configuration for files which no override section applies
+    /// to from the contents of a configuration file.
+    pub fn parse(source: &str) -> Result<Self, ConfigError> {
+        ConfigFile::parse(source).map(|file| file.config)
+    }
+
+    /// Read and parse the configuration for files which no override section
+    /// applies to from a configuration file.
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        ConfigFile::load(path).map(|file| file.config)
+    }
+
+    /// Find the configuration file which applies to files in `dir`, by
+    /// searching it and then each of its ancestors.
+    pub fn find(dir: &Path) -> Option<PathBuf> {
+        dir.ancestors()
+            .map(|dir| dir.join(FILE_NAME))
+            .find(|path| path.is_file())
+    }
+}
+
+/// A configuration file, with the [`Config`] for the files it applies to
+/// and any sections which override it for some of them.
+///
+/// Paths are matched relative to the directory of the configuration file by
+/// glob patterns, in which `*` doesn't match `/`. A pattern which matches a
+/// directory applies to everything in it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConfigFile {
+    /// Configuration for files which no override section applies to.
+    pub config: Config,
+    /// Settings which override the configuration for matching paths, in the
+    /// order they apply.
+    overrides: Vec<(Pattern, Vec<Setting>)>,
+    /// Paths which shouldn't be linted at all.
+    ignore: Vec<Pattern>,
+}
+
+impl ConfigFile {
+    /// Parse the contents of a configuration file.
+    pub fn parse(source: &str) -> Result<Self, ConfigError> {
+        let root = Parser::parse(source).map_err(ConfigError::Parse)?;
+        let mut file = Self::default();
+
+        let mut members = Vec::new();
+        for member in &root.members {
+            let member = &member.inner;
+            let (value, at) = value_text(source, &member.value);
+
+            match text(source, &member.key).as_str() {
+                "extends" => {
+                    file.config =
+                        Config::preset(&value).ok_or_else(|| ConfigError::InvalidValue {
+                            name: String::from("extends"),
+                            value,
+                            expected: PRESET_NAMES,
+                            at,
+                        })?
+                }
+                "overrides" => file.overrides = overrides(source, &member.value)?,
+                "ignore" => file.ignore = patterns(source, &member.value)?,
+                _ => members.push(member),
+            }
+        }
+
+        // Apply any preset first so that the other rules override it.
+        for setting in settings(source, &members, false)? {
+            setting.apply(&mut file.config);
+        }
+
+        Ok(file)
+    }
+
+    /// Read and parse a configuration file.
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let source = fs::read_to_string(path).map_err(ConfigError::Io)?;
+        Self::parse(&source)
+    }
+
+    /// Configuration for the file at `path`, relative to the directory of the
+    /// configuration file, with every matching override section applied.
+    pub fn config_for(&self, path: &Path) -> Config {
+        let mut config = self.config.clone();
+
+        let matching = self
+            .overrides
+            .iter()
+            .filter(|(pattern, _)| matches(pattern, path));
+        for setting in matching.flat_map(|(_, settings)| settings) {
+            setting.apply(&mut config);
+        }
+
+        config
+    }
+
+    /// Whether the file at `path`, relative to the directory of the
+    /// configuration file, is ignored.
+    pub fn ignores(&self, path: &Path) -> bool {
+        self.ignore.iter().any(|pattern| matches(pattern, path))
+    }
+}
+
+impl From<Config> for ConfigFile {
+    /// A configuration file which applies `config` to every file.
+    fn from(config: Config) -> Self {
+        Self {
+            config,
+            ..Self::default()
+        }
+    }
+}
+
+/// A rule set in a configuration file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) enum Setting {
+    AllowDeny(LintKind, AllowDeny),
+    AllowDenyRequire(LintKind, AllowDenyRequire),
+    Level(String, Level),
+    Scope(Scope),
 }
 
+impl Setting {
+    /// Apply the setting, unless its rule is forbidden.
+    pub(super) fn apply(&self, config: &mut Config) {
+        match self {
+            Setting::AllowDeny(kind, state) if !config.forbids(kind.name()) => {
+                if let Some(field) = config.allow_deny_mut(*kind) {
+                    *field = *state;
+                }
+            }
+            Setting::AllowDenyRequire(kind, state) if !config.forbids(kind.name()) => {
+                if let Some(field) = config.allow_deny_require_mut(*kind) {
+                    *field = *state;
+                }
+            }
+            Setting::Level(rule, level) => config.set_level(rule, *level),
+            Setting::Scope(scope) => config.scopes.push(scope.clone()),
+            _ => (),
+        }
+    }
+}
+
+/// Parse the rules set by members of a configuration map, with any `levels`
+/// last so that they can turn on allowed rules. Maps which are already in a
+/// scope can't have `scopes` of their own.
+fn settings(
+    source: &str,
+    members: &[&MapMember],
+    scoped: bool,
+) -> Result<Vec<Setting>, ConfigError> {
+    let mut settings = Vec::new();
+    let mut levels = Vec::new();
+
+    for member in members {
+        let name = text(source, &member.key);
+        let (value, at) = value_text(source, &member.value);
+        let invalid = |expected| ConfigError::InvalidValue {
+            name: name.clone(),
+            value: value.clone(),
+            expected,
+            at,
+        };
+
+        let allow_deny = |kind| {
+            let state = value.parse().map_err(|_| invalid("`allow` or `deny`"))?;
+            Ok(Setting::AllowDeny(kind, state))
+        };
+        let allow_deny_require = |kind| {
+            let state = value
+                .parse()
+                .map_err(|_| invalid("`allow`, `deny`, or `require`"))?;
+            Ok(Setting::AllowDenyRequire(kind, state))
+        };
+
+        let setting = match name.as_str() {
+            "levels" => match &member.value {
+                Value::Map(map) => {
+                    levels.extend(&map.members);
+                    continue;
+                }
+                _ => Err(invalid("a map of rule names to levels")),
+            },
+            "scopes" if !scoped => {
+                settings.extend(scopes(source, &member.value)?);
+                continue;
+            }
+            rule => match setting_kind(rule) {
+                Some(
+                    kind @ (LintKind::ImplicitBraces
+                    | LintKind::TrailingComma
+                    | LintKind::UnquotedValue
+                    | LintKind::UnquotedKey),
+                ) => allow_deny_require(kind),
+                Some(kind) => allow_deny(kind),
+                None => Err(ConfigError::UnknownRule {
+                    name,
+                    at: member.key.start,
+                }),
+            },
+        };
+        settings.push(setting?);
+    }
+
+    for member in levels {
+        let name = text(source, &member.inner.key);
+        let (value, at) = value_text(source, &member.inner.value);
+
+        if LintKind::from_name(&name).is_none() {
+            return Err(ConfigError::UnknownRule {
+                name,
+                at: member.inner.key.start,
+            });
+        }
+        let level = value.parse().map_err(|_| ConfigError::InvalidValue {
+            name: name.clone(),
+            value,
+            expected: "`allow`, `warn`, `deny`, or `forbid`",
+            at,
+        })?;
+
+        settings.push(Setting::Level(name, level));
+    }
+
+    Ok(settings)
+}
+
+/// The kind of lint a setting configures, given the name of its field in
+/// [`Config`] or the rule's own name, such as `trailing_commas` or
+/// `trailing-comma`.
+fn setting_kind(name: &str) -> Option<LintKind> {
+    let kind = match name {
+        "trailing_whitespace" => LintKind::TrailingWhitespace,
+        "root_braces" => LintKind::ImplicitBraces,
+        "missing_commas" => LintKind::MissingComma,
+        "trailing_commas" => LintKind::TrailingComma,
+        "unquoted_values" => LintKind::UnquotedValue,
+        "unquoted_keys" => LintKind::UnquotedKey,
+        "duplicate_keys" => LintKind::DuplicateKey,
+        "unused_suppressions" => LintKind::UnusedSuppression,
+        _ => return LintKind::from_name(name),
+    };
+
+    Some(kind)
+}
+
+/// Parse the `overrides` map of path patterns to the rules they set.
+fn overrides(source: &str, value: &Value) -> Result<Vec<(Pattern, Vec<Setting>)>, ConfigError> {
+    let invalid = |name: &str, value, expected| {
+        let (value, at) = value_text(source, value);
+        ConfigError::InvalidValue {
+            name: name.to_string(),
+            value: value.to_string(),
+            expected,
+            at,
+        }
+    };
+
+    let Value::Map(map) = value else {
+        return Err(invalid(
+            "overrides",
+            value,
+            "a map of path patterns to rules",
+        ));
+    };
+
+    map.members
+        .iter()
+        .map(|member| {
+            let member = &member.inner;
+            let Value::Map(section) = &member.value else {
+                let name = text(source, &member.key);
+                return Err(invalid(&name, &member.value, "a map of rules"));
+            };
+
+            let members: Vec<_> = section.members.iter().map(|member| &member.inner).collect();
+            Ok((
+                pattern(source, &member.key)?,
+                settings(source, &members, false)?,
+            ))
+        })
+        .collect()
+}
+
+/// Parse the `scopes` map of key path patterns to the rules they set.
+fn scopes(source: &str, value: &Value) -> Result<Vec<Setting>, ConfigError> {
+    let invalid = |name: &str, value, expected| {
+        let (value, at) = value_text(source, value);
+        ConfigError::InvalidValue {
+            name: name.to_string(),
+            value: value.to_string(),
+            expected,
+            at,
+        }
+    };
+
+    let Value::Map(map) = value else {
+        return Err(invalid(
+            "scopes",
+            value,
+            "a map of key path patterns to rules",
+        ));
+    };
+
+    map.members
+        .iter()
+        .map(|member| {
+            let member = &member.inner;
+            let pattern = text(source, &member.key);
+            let Value::Map(section) = &member.value else {
+                return Err(invalid(&pattern, &member.value, "a map of rules"));
+            };
+
+            let members: Vec<_> = section.members.iter().map(|member| &member.inner).collect();
+            let settings = settings(source, &members, true)?;
+            let scope =
+                Scope::new(&pattern, settings).map_err(|message| ConfigError::InvalidPattern {
+                    pattern,
+                    message,
+                    at: member.key.start,
+                })?;
+
+            Ok(Setting::Scope(scope))
+        })
+        .collect()
+}
+
+/// Parse the `ignore` array of path patterns.
+fn patterns(source: &str, value: &Value) -> Result<Vec<Pattern>, ConfigError> {
+    let invalid = |value| {
+        let (value, at) = value_text(source, value);
+        ConfigError::InvalidValue {
+            name: String::from("ignore"),
+            value: value.to_string(),
+            expected: "an array of path patterns",
+            at,
+        }
+    };
+
+    let Value::Array(array) = value else {
+        return Err(invalid(value));
+    };
+
+    array
+        .members
+        .iter()
+        .map(|member| match &member.inner.value {
+            Value::Value(span) => pattern(source, span),
+            value => Err(invalid(value)),
+        })
+        .collect()
+}
+
+/// Parse a path pattern from a key or value.
+fn pattern(source: &str, span: &Span) -> Result<Pattern, ConfigError> {
+    let pattern = text(source, span);
+    Pattern::new(&pattern).map_err(|err| ConfigError::InvalidPattern {
+        pattern,
+        message: err.msg,
+        at: span.start,
+    })
+}
+
+/// Whether a pattern matches a path or any of the directories it's in.
+fn matches(pattern: &Pattern, path: &Path) -> bool {
+    let options = MatchOptions {
+        require_literal_separator: true,
+        ..MatchOptions::new()
+    };
+
+    path.ancestors()
+        .filter(|path| !path.as_os_str().is_empty())
+        .any(|path| pattern.matches_path_with(path, options))
+}
+
+/// Text of a key or value, decoded if it's quoted.
+fn text(source: &str, span: &Span) -> String {
+    let start = span.start.byte_offset;
+    let text = &source[start..start + span.len];
+
+    match span.kind {
+        TokenKind::TextSingle | TokenKind::TextDouble => {
+            hjson_parser::value::unquote(text).unwrap_or_else(|_| text.to_string())
+        }
+        _ => text.to_string(),
+    }
+}
+
+/// Text of a value and where it starts. Maps and arrays are abbreviated.
+fn value_text(source: &str, value: &Value) -> (String, Cursor) {
+    match value {
+        Value::Value(span) => (text(source, span), span.start),
+        Value::Map(map) => (
+            String::from("{ ... }"),
+            map.open_brace
+                .inner
+                .as_ref()
+                .map_or(Cursor::default(), |brace| brace.start),
+        ),
+        Value::Array(array) => (String::from("[ ... ]"), array.open_bracket.inner.start),
+    }
+}
+
+/// Errors from loading configuration files.
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The file could not be read.
+    Io(io::Error),
+    /// The file is not valid Hjson.
+    Parse(ParseError),
+    /// The file configures a rule that doesn't exist.
+    UnknownRule { name: String, at: Cursor },
+    /// The file gives an invalid value for a rule.
+    InvalidValue {
+        name: String,
+        value: String,
+        expected: &'static str,
+        at: Cursor,
+    },
+    /// The file gives an invalid path pattern.
+    InvalidPattern {
+        pattern: String,
+        message: &'static str,
+        at: Cursor,
+    },
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(err) => write!(f, "{err}"),
+            ConfigError::Parse(err) => write!(f, "{err}"),
+            ConfigError::UnknownRule { name, at } => {
+                write!(f, "{}:{}: unknown rule `{name}`", at.line, at.column)
+            }
+            ConfigError::InvalidValue {
+                name,
+                value,
+                expected,
+                at,
+            } => write!(
+                f,
+                "{}:{}: invalid value `{value}` for `{name}`, expected {expected}",
+                at.line, at.column
+            ),
+            ConfigError::InvalidPattern {
+                pattern,
+                message,
+                at,
+            } => write!(
+                f,
+                "{}:{}: invalid pattern `{pattern}`: {message}",
+                at.line, at.column
+            ),
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
 /// States for allowing or denying some rule.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum AllowDeny {
@@ -66,3 +785,525 @@ pub enum AllowDenyRequire {
     Allow,
     Deny,
 }
+
+impl AllowDeny {
+    /// This state turned on or off, denying it if it was allowed.
+    fn enabled(self, on: bool) -> Self {
+        match (self, on) {
+            (_, false) => Self::Allow,
+            (Self::Allow, true) => Self::Deny,
+            (state, true) => state,
+        }
+    }
+}
+
+impl AllowDenyRequire {
+    /// This state turned on or off, denying it if it was allowed.
+    fn enabled(self, on: bool) -> Self {
+        match (self, on) {
+            (_, false) => Self::Allow,
+            (Self::Allow, true) => Self::Deny,
+            (state, true) => state,
+        }
+    }
+}
+
+impl FromStr for AllowDeny {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "allow" => Ok(Self::Allow),
+            "deny" => Ok(Self::Deny),
+            _ => Err(()),
+        }
+    }
+}
+
+impl FromStr for AllowDenyRequire {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "require" => Ok(Self::Require),
+            "allow" => Ok(Self::Allow),
+            "deny" => Ok(Self::Deny),
+            _ => Err(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::linter::Linter;
+    use indoc::indoc;
+
+    #[test]
+    fn empty() {
+        assert_eq!(Config::parse("{}").unwrap(), Config::default());
+        assert_eq!(Config::parse("").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn rules() {
+        let config = Config::parse(indoc! {r#"
+            {
+              trailing_whitespace: deny
+              'root_braces': require
+              "trailing_commas": "deny"
+              unquoted_keys: 'allow'
+              duplicate_keys: deny
+              "unquoted\u005fvalues": 'd\u0065ny'
+            }
+        "#})
+        .unwrap();
+
+        assert_eq!(
+            config,
+            Config {
+                trailing_whitespace: AllowDeny::Deny,
+                duplicate_keys: AllowDeny::Deny,
+                unquoted_values: AllowDenyRequire::Deny,
+                root_braces: AllowDenyRequire::Require,
+                trailing_commas: AllowDenyRequire::Deny,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn rule_names() {
+        // Rules can also be set by the names used everywhere else.
+        let config = Config::parse(indoc! {"
+            implicit-braces: require
+            trailing-comma: deny
+            duplicate-key: deny
+        "})
+        .unwrap();
+        let fields = Config::parse(indoc! {"
+            root_braces: require
+            trailing_commas: deny
+            duplicate_keys: deny
+        "})
+        .unwrap();
+        assert_eq!(config, fields);
+        assert_ne!(config, Config::default());
+
+        let err = Config::parse("missing-comma: require").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "1:16: invalid value `require` for `missing-comma`, expected `allow` or `deny`"
+        );
+    }
+
+    #[test]
+    fn extends() {
+        // Presets apply before other rules, wherever they're written.
+        let config = Config::parse(indoc! {"
+            unquoted_keys: require
+            extends: strict
+        "})
+        .unwrap();
+
+        assert_eq!(
+            config,
+            Config {
+                unquoted_keys: AllowDenyRequire::Require,
+                ..Config::strict()
+            }
+        );
+
+        let err = Config::parse("extends: lax").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "1:10: invalid value `lax` for `extends`, expected `default`, `strict`, `jsonc`, `hjson-canonical`, or `relaxed-hjson`"
+        );
+    }
+
+    /// Representative documents in each dialect a preset is for.
+    const JSON: &str = indoc! {r#"
+        {
+          // Comments aren't linted.
+          "name": "my server",
+          "ports": [80, 443],
+          "limits": { "min": -1.5, "max": null },
+          "debug": false
+        }
+    "#};
+
+    const JSONC: &str = indoc! {r#"
+        {
+          // Editor settings.
+          "editor.tabSize": 2,
+          "files.exclude": {
+            "**/.git": true,
+          },
+        }
+    "#};
+
+    const HJSON_CANONICAL: &str = indoc! {r#"
+        {
+          name: my server
+          "quoted key": 1
+          empty: ""
+          ports:
+          [
+            80
+            443
+          ]
+          description:
+            '''
+            first line
+            second line
+            '''
+          limits:
+          {
+            min: -1.5
+            max: null
+          }
+        }
+    "#};
+
+    /// Minified JSON, without any whitespace around commas.
+    const COMPACT_JSON: &str = r#"{"a":1,"b":[1,2],"c":{"d":null,"e":"f"}}"#;
+
+    const RELAXED_HJSON: &str = indoc! {r#"
+        # No braces, and any mix of commas and quotes.
+        name: my server
+        'port': 8080,
+        tags: [
+          "a", "b"
+          c
+        ]
+        limits: {
+          "min": -1.5, max: 10,
+        }
+    "#};
+
+    #[test]
+    fn presets() {
+        let lints = |name, input| {
+            let config = Config::preset(name).unwrap();
+            let mut kinds: Vec<_> = Linter::lint(&config, input)
+                .unwrap()
+                .iter()
+                .map(|lint| lint.kind().name())
+                .collect();
+            kinds.sort();
+            kinds.dedup();
+            kinds
+        };
+
+        // Each preset passes documents in its own dialect, and any stricter one.
+        let cases: &[(&str, &str, &[&str])] = &[
+            ("strict", JSON, &[]),
+            ("strict", COMPACT_JSON, &[]),
+            ("jsonc", COMPACT_JSON, &[]),
+            ("hjson-canonical", COMPACT_JSON, &["unquoted-key"]),
+            ("strict", JSONC, &["trailing-comma"]),
+            ("jsonc", JSON, &[]),
+            ("jsonc", JSONC, &[]),
+            (
+                "jsonc",
+                HJSON_CANONICAL,
+                &["missing-comma", "unquoted-key", "unquoted-value"],
+            ),
+            ("hjson-canonical", HJSON_CANONICAL, &[]),
+            ("hjson-canonical", JSON, &["unquoted-key"]),
+            (
+                "hjson-canonical",
+                RELAXED_HJSON,
+                &[
+                    "implicit-braces",
+                    "trailing-comma",
+                    "unquoted-key",
+                    "unquoted-value",
+                ],
+            ),
+            ("relaxed-hjson", RELAXED_HJSON, &[]),
+            ("relaxed-hjson", "a: 1\nb: 2\n'a': 3", &["duplicate-key"]),
+        ];
+        for (name, input, expected) in cases {
+            assert_eq!(lints(name, input), *expected, "{name}:\n{input}");
+        }
+
+        for input in [JSON, COMPACT_JSON, JSONC, HJSON_CANONICAL, RELAXED_HJSON] {
+            assert_eq!(lints("default", input), Vec::<&str>::new());
+            assert_eq!(lints("relaxed-hjson", input), Vec::<&str>::new());
+        }
+
+        for name in PRESETS {
+            let config = Config::parse(&format!("extends: {name}")).unwrap();
+            assert_eq!(Some(config), Config::preset(name));
+        }
+        assert!(Config::preset("json").is_none());
+    }
+
+    #[test]
+    fn levels() {
+        let config = Config::parse(indoc! {"
+            levels: {
+              missing-comma: warn
+              'trailing-comma': forbid
+            }
+            trailing_commas: require
+            duplicate_keys: deny
+        "})
+        .unwrap();
+
+        // Levels turn on rules which were allowed, keeping any requirement.
+        assert_eq!(config.missing_commas, AllowDeny::Deny);
+        assert_eq!(config.trailing_commas, AllowDenyRequire::Require);
+        assert_eq!(config.level(LintKind::MissingComma), Level::Warn);
+        assert_eq!(config.level(LintKind::TrailingComma), Level::Forbid);
+        assert_eq!(config.level(LintKind::DuplicateKey), Level::Deny);
+        assert_eq!(config.level(LintKind::UnquotedKey), Level::Allow);
+
+        // Forbidden rules can't be changed, but others can be turned off.
+        let mut config = config;
+        config.set_level("trailing-comma", Level::Allow);
+        config.set_level("missing-comma", Level::Allow);
+        assert_eq!(config.level(LintKind::TrailingComma), Level::Forbid);
+        assert_eq!(config.level(LintKind::MissingComma), Level::Allow);
+        assert_eq!(config.missing_commas, AllowDeny::Allow);
+
+        let err = Config::parse("levels: { trailing_comma: 'warn' }").unwrap_err();
+        assert_eq!(err.to_string(), "1:11: unknown rule `trailing_comma`");
+
+        let err = Config::parse("levels: { trailing-comma: 'error' }").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "1:27: invalid value `error` for `trailing-comma`, expected `allow`, `warn`, `deny`, or `forbid`"
+        );
+
+        let err = Config::parse("levels: warn").unwrap_err();
+        assert!(matches!(err, ConfigError::InvalidValue { .. }));
+    }
+
+    #[test]
+    fn errors() {
+        let err = Config::parse(
+            "missing_commas: deny
+trailing_comma: deny",
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), "2:1: unknown rule `trailing_comma`");
+
+        let err = Config::parse("missing_commas: require").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "1:17: invalid value `require` for `missing_commas`, expected `allow` or `deny`"
+        );
+
+        let err = Config::parse("root_braces: { a: 'b' }").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "1:14: invalid value `{ ... }` for `root_braces`, expected `allow`, `deny`, or `require`"
+        );
+
+        assert!(matches!(
+            Config::parse("root_braces: "),
+            Err(ConfigError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn overrides() {
+        let file = ConfigFile::parse(indoc! {r#"
+            extends: strict
+            overrides: {
+              "generated/**": {
+                unquoted_keys: allow
+                trailing_commas: require
+              }
+              "generated/legacy/*.hjson": {
+                trailing_commas: allow
+                levels: { missing-comma: 'warn' }
+              }
+              docs: { root_braces: 'deny' }
+            }
+            ignore: ["vendor", "*.min.hjson"]
+        "#})
+        .unwrap();
+        assert_eq!(file.config, Config::strict());
+
+        let config = file.config_for(Path::new("a.hjson"));
+        assert_eq!(config, Config::strict());
+
+        let config = file.config_for(Path::new("generated/a/b.hjson"));
+        assert_eq!(config.unquoted_keys, AllowDenyRequire::Allow);
+        assert_eq!(config.trailing_commas, AllowDenyRequire::Require);
+
+        // Later sections take precedence over earlier ones.
+        let config = file.config_for(Path::new("generated/legacy/a.hjson"));
+        assert_eq!(config.unquoted_keys, AllowDenyRequire::Allow);
+        assert_eq!(config.trailing_commas, AllowDenyRequire::Allow);
+        assert_eq!(config.level(LintKind::MissingComma), Level::Warn);
+
+        // `*` doesn't match `/`, and directories match everything in them.
+        let config = file.config_for(Path::new("generated/legacy/a/b.hjson"));
+        assert_eq!(config.trailing_commas, AllowDenyRequire::Require);
+        assert_eq!(
+            file.config_for(Path::new("docs/a.hjson")).root_braces,
+            AllowDenyRequire::Deny
+        );
+
+        assert!(file.ignores(Path::new("vendor/a/b.hjson")));
+        assert!(file.ignores(Path::new("a.min.hjson")));
+        assert!(!file.ignores(Path::new("a/b.min.hjson")));
+        assert!(!file.ignores(Path::new("a.hjson")));
+    }
+
+    #[test]
+    fn overrides_forbidden() {
+        let file = ConfigFile::parse(indoc! {"
+            levels: { unquoted-key: 'forbid' }
+            overrides: {
+              '*': {
+                unquoted_keys: allow
+                levels: { unquoted-key: 'allow' }
+              }
+            }
+        "})
+        .unwrap();
+
+        let config = file.config_for(Path::new("a.hjson"));
+        assert_eq!(config.unquoted_keys, AllowDenyRequire::Deny);
+        assert_eq!(config.level(LintKind::UnquotedKey), Level::Forbid);
+    }
+
+    #[test]
+    fn overrides_errors() {
+        let err = ConfigFile::parse("overrides: { '*': { trailing_comma: 'deny' } }").unwrap_err();
+        assert_eq!(err.to_string(), "1:21: unknown rule `trailing_comma`");
+
+        let err = ConfigFile::parse("overrides: { '*': 'deny' }").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "1:19: invalid value `deny` for `*`, expected a map of rules"
+        );
+
+        let err = ConfigFile::parse("overrides: deny").unwrap_err();
+        assert!(matches!(err, ConfigError::InvalidValue { .. }));
+
+        let err = ConfigFile::parse("ignore: vendor").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "1:9: invalid value `vendor` for `ignore`, expected an array of path patterns"
+        );
+
+        let err = ConfigFile::parse("ignore: ['a/***']").unwrap_err();
+        assert!(matches!(err, ConfigError::InvalidPattern { .. }));
+    }
+
+    #[test]
+    fn scopes() {
+        let file = ConfigFile::parse(indoc! {r#"
+            scopes: {
+              "/a/*": { unquoted_keys: "deny" }
+            }
+            overrides: {
+              "*.hjson": {
+                scopes: { "/b": { levels: { unquoted-key: "warn" } } }
+              }
+            }
+        "#})
+        .unwrap();
+
+        let patterns: Vec<_> = file.config.scopes.iter().map(Scope::pattern).collect();
+        assert_eq!(patterns, ["/a/*"]);
+
+        let config = file.config_for(Path::new("a.hjson"));
+        let patterns: Vec<_> = config.scopes.iter().map(Scope::pattern).collect();
+        assert_eq!(patterns, ["/a/*", "/b"]);
+
+        // Scopes change a copy of the configuration they're in.
+        let mut scoped = config.clone();
+        config.scopes[1].apply(&mut scoped);
+        assert_eq!(scoped.level(LintKind::UnquotedKey), Level::Warn);
+        assert_eq!(config.level(LintKind::UnquotedKey), Level::Allow);
+
+        let err = Config::parse("scopes: { '/a': { scopes: {} } }").unwrap_err();
+        assert_eq!(err.to_string(), "1:19: unknown rule `scopes`");
+
+        let err = Config::parse("scopes: { 'a': {} }").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "1:11: invalid pattern `a`: patterns must be empty or start with `/`"
+        );
+
+        let err = Config::parse("scopes: ['/a']").unwrap_err();
+        assert!(matches!(err, ConfigError::InvalidValue { .. }));
+    }
+
+    #[test]
+    fn serializer_options() {
+        let value = serde_json::json!({
+            "name": "my server",
+            "quoted key": "a, b",
+            "description": "first line\n  indented\n\nlast",
+            "escaped": "say \"hi\"",
+            "ports": [80, 443],
+            "limits": { "min": -1.5, "max": null, "none": {} },
+            "tags": [],
+            "debug": true,
+        });
+
+        let configs = [
+            Config::default(),
+            Config::strict(),
+            Config::jsonc(),
+            Config::hjson_canonical(),
+            Config::relaxed_hjson(),
+            Config {
+                trailing_whitespace: AllowDeny::Deny,
+                root_braces: AllowDenyRequire::Deny,
+                missing_commas: AllowDeny::Allow,
+                trailing_commas: AllowDenyRequire::Deny,
+                unquoted_values: AllowDenyRequire::Require,
+                unquoted_keys: AllowDenyRequire::Require,
+                duplicate_keys: AllowDeny::Deny,
+                unused_suppressions: AllowDeny::Deny,
+                levels: BTreeMap::new(),
+                scopes: Vec::new(),
+            },
+            Config {
+                trailing_commas: AllowDenyRequire::Require,
+                unquoted_values: AllowDenyRequire::Require,
+                ..Config::strict()
+            },
+        ];
+
+        for config in configs {
+            let output =
+                hjson_parser::ser::to_string_with(&value, &config.serializer_options()).unwrap();
+            assert_eq!(
+                Linter::lint(&config, &output).unwrap(),
+                Vec::new(),
+                "{output}"
+            );
+
+            let decoded: serde_json::Value = hjson_parser::from_str(&output).unwrap();
+            assert_eq!(decoded, value);
+        }
+    }
+
+    #[test]
+    fn find() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path();
+        let nested = root.join("a/b");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(root.join(FILE_NAME), "extends: strict").unwrap();
+
+        assert_eq!(Config::find(&nested), Some(root.join(FILE_NAME)));
+        assert_eq!(
+            Config::load(&root.join(FILE_NAME)).unwrap(),
+            Config::strict()
+        );
+
+        fs::write(nested.join(FILE_NAME), "").unwrap();
+        assert_eq!(Config::find(&nested), Some(nested.join(FILE_NAME)));
+    }
+}
@@ -0,0 +1,198 @@
+//! Explanations of the built-in rules, as printed by `hjson-lint --explain`.
+//!
+//! Each explanation's examples are written for the [`strict`] configuration,
+//! so the bad example breaks the rule and the good example passes.
+//!
+//! [`strict`]: super::Config::strict
+
+use std::fmt::{self, Display};
+
+use super::LintKind;
+
+/// Why a built-in rule exists, with examples of breaking and following it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Explanation {
+    /// The kind of lint the rule reports.
+    pub kind: LintKind,
+    /// Paragraphs explaining why the rule exists and how it's configured.
+    pub rationale: &'static str,
+    /// A document which breaks the rule.
+    pub bad: &'static str,
+    /// The same document changed to follow the rule.
+    pub good: &'static str,
+}
+
+impl Explanation {
+    /// Explanation of a kind of lint, if it's from a built-in rule.
+    pub fn of(kind: LintKind) -> Option<Self> {
+        let (rationale, bad, good) = match kind {
+            LintKind::ImplicitBraces => (
+                "Hjson allows the braces around the root map to be left out, but JSON and \
+                many other parsers require them.\n\n\
+                With `root_braces: require`, documents without braces are reported. With \
+                `root_braces: deny`, braces around the root map are reported instead.",
+                "\"name\": \"my server\"\n",
+                "{\n  \"name\": \"my server\"\n}\n",
+            ),
+            LintKind::MissingComma => (
+                "Hjson allows the commas between members to be left out at the ends of \
+                lines, but JSON requires them.\n\n\
+                With `missing_commas: deny`, members which are only separated by a new \
+                line are reported.",
+                "{\n  \"name\": \"my server\"\n  \"port\": 8080\n}\n",
+                "{\n  \"name\": \"my server\",\n  \"port\": 8080\n}\n",
+            ),
+            LintKind::TrailingComma => (
+                "Hjson allows a comma after the last member of a map or array, but JSON \
+                doesn't.\n\n\
+                With `trailing_commas: deny`, commas after the last member are reported. \
+                With `trailing_commas: require`, their absence is reported instead, which \
+                keeps diffs small when members are added.",
+                "{\n  \"ports\": [\n    80,\n    443,\n  ]\n}\n",
+                "{\n  \"ports\": [\n    80,\n    443\n  ]\n}\n",
+            ),
+            LintKind::TrailingWhitespace => (
+                "Whitespace at the ends of lines is invisible, so it's easily added by \
+                mistake and makes for noisy diffs. In unquoted strings it can also be \
+                mistaken for part of the value.\n\n\
+                With `trailing_whitespace: deny`, spaces and tabs before the end of a line \
+                are reported. The bad example has spaces after `8080`.",
+                "{\n  \"port\": 8080   \n}\n",
+                "{\n  \"port\": 8080\n}\n",
+            ),
+            LintKind::UnquotedKey => (
+                "Hjson allows keys without quotes, but JSON requires them to be quoted.\n\n\
+                With `unquoted_keys: deny`, keys without quotes are reported. With \
+                `unquoted_keys: require`, quoted keys which could be unquoted are reported \
+                instead.",
+                "{\n  name: \"my server\"\n}\n",
+                "{\n  \"name\": \"my server\"\n}\n",
+            ),
+            LintKind::UnquotedValue => (
+                "Hjson allows strings without quotes, which run to the end of the line. \
+                JSON requires them to be quoted, and it's easy to add a comma or comment \
+                to an unquoted string by mistake.\n\n\
+                With `unquoted_values: deny`, unquoted strings are reported. With \
+                `unquoted_values: require`, quoted strings which could be unquoted are \
+                reported instead.",
+                "{\n  \"name\": my server\n}\n",
+                "{\n  \"name\": \"my server\"\n}\n",
+            ),
+            LintKind::DuplicateKey => (
+                "When a key appears more than once in a map, parsers disagree on which \
+                value to use, and some reject the document. Keys are compared after \
+                decoding, so `a`, `'a'`, and `\"a\"` are the same key.\n\n\
+                With `duplicate_keys: deny`, every repetition of a key is reported.",
+                "{\n  \"port\": 80,\n  \"port\": 8080\n}\n",
+                "{\n  \"port\": 8080\n}\n",
+            ),
+            LintKind::UnusedSuppression => (
+                "Suppression comments which don't suppress any lints are likely to be left \
+                over from lints which were fixed, and would hide new lints if they were \
+                kept.\n\n\
+                With `unused_suppressions: deny`, each rule named by a suppression comment \
+                which didn't suppress anything is reported, unless the rule is allowed.",
+                "{\n  // hjson-lint: allow(unquoted-key)\n  \"name\": \"my server\"\n}\n",
+                "{\n  \"name\": \"my server\"\n}\n",
+            ),
+            LintKind::Custom { .. } => return None,
+        };
+
+        Some(Self {
+            kind,
+            rationale,
+            bad,
+            good,
+        })
+    }
+}
+
+impl Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = self.kind.name();
+        match self.kind.code() {
+            Some(code) => writeln!(f, "{name} ({code}): {}", self.kind)?,
+            None => writeln!(f, "{name}: {}", self.kind)?,
+        }
+
+        for paragraph in self.rationale.split("\n\n") {
+            writeln!(f)?;
+            wrap(f, paragraph)?;
+        }
+
+        writeln!(f, "\nBad:\n")?;
+        for line in self.bad.lines() {
+            writeln!(f, "    {line}")?;
+        }
+
+        writeln!(f, "\nGood:\n")?;
+        for line in self.good.lines() {
+            writeln!(f, "    {line}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Write a paragraph with its lines wrapped to 80 columns.
+fn wrap(f: &mut fmt::Formatter<'_>, paragraph: &str) -> fmt::Result {
+    let mut width = 0;
+    for word in paragraph.split_whitespace() {
+        if width > 0 && width + 1 + word.len() > 80 {
+            writeln!(f)?;
+            width = 0;
+        }
+        if width > 0 {
+            f.write_str(" ")?;
+            width += 1;
+        }
+        f.write_str(word)?;
+        width += word.len();
+    }
+
+    writeln!(f)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::linter::{Config, Linter};
+
+    #[test]
+    fn every_rule() {
+        let config = Config::strict();
+
+        for &kind in LintKind::ALL {
+            let explanation = Explanation::of(kind)
+                .unwrap_or_else(|| panic!("`{}` has no explanation", kind.name()));
+
+            let bad = Linter::lint(&config, explanation.bad).unwrap();
+            assert!(
+                bad.iter().any(|lint| lint.kind() == kind),
+                "bad example for `{}` isn't linted: {bad:?}",
+                kind.name()
+            );
+
+            let good = Linter::lint(&config, explanation.good).unwrap();
+            assert_eq!(good, Vec::new(), "good example for `{}`", kind.name());
+        }
+
+        let custom = LintKind::Custom {
+            rule: "no-todo",
+            message: "unfinished work",
+        };
+        assert_eq!(Explanation::of(custom), None);
+    }
+
+    #[test]
+    fn display() {
+        let explanation = Explanation::of(LintKind::TrailingComma).unwrap();
+        let text = explanation.to_string();
+
+        assert!(text.starts_with("trailing-comma (HL003): trailing comma\n\n"));
+        assert!(text.lines().all(|line| line.len() <= 80));
+        assert!(text.ends_with(
+            "Good:\n\n    {\n      \"ports\": [\n        80,\n        443\n      ]\n    }\n"
+        ));
+    }
+}
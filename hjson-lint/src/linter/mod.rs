@@ -1,18 +1,32 @@
-mod config;
+mod builtin;
+pub mod config;
+mod explain;
+pub mod rule;
+pub mod scope;
+mod suppress;
 
+use std::borrow::Cow;
 use std::fmt::{self, Display};
 
-use crate::lexer::{Cursor, Span, TokenKind};
-use crate::parser::ast::{Array, ArrayMember, Map, MapMember, Node, Value};
+use crate::fix::Fix;
+use crate::lexer::{Cursor, Span};
+use crate::parser::ast::{Array, Map, Node, Value};
 use crate::parser::{ParseError, Parser};
 
-pub use self::config::Config;
-use self::config::{AllowDeny, AllowDenyRequire};
+use self::suppress::Suppression;
+
+pub use self::config::{AllowDeny, AllowDenyRequire, Config, ConfigError, ConfigFile};
+pub use self::explain::Explanation;
+pub use self::rule::{Level, Registry, Rule};
+pub use self::scope::Scope;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Lint {
     kind: LintKind,
+    level: Level,
     span: LintSpan,
+    fix: Option<Fix>,
+    details: Details,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -21,12 +35,198 @@ pub struct LintSpan {
     len: usize,
 }
 
+/// Information about a lint beyond its primary span.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Details {
+    /// Other parts of the source involved in the lint, each labelled with how.
+    pub labels: Vec<Label>,
+    /// Free-form notes explaining the lint.
+    pub notes: Vec<String>,
+    /// A suggestion of how to resolve the lint.
+    pub help: Option<String>,
+}
+
+/// A secondary span related to a lint, labelled with how it's involved.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Label {
+    message: String,
+    span: LintSpan,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum LintKind {
     ImplicitBraces,
     MissingComma,
     TrailingComma,
     TrailingWhitespace,
+    UnquotedKey,
+    UnquotedValue,
+    DuplicateKey,
+    UnusedSuppression,
+    /// A lint from a [`Rule`] which isn't built in.
+    Custom {
+        /// Name of the rule which was broken.
+        rule: &'static str,
+        /// Description of what was found, e.g. `long array`.
+        message: &'static str,
+    },
+}
+
+impl Lint {
+    /// A denied lint of the given kind, without a fix or any details.
+    ///
+    /// Lints reported by rules are given the level of the rule.
+    pub fn new(kind: LintKind, start: Cursor, len: usize) -> Self {
+        Self {
+            kind,
+            level: Level::Deny,
+            span: LintSpan { start, len },
+            fix: None,
+            details: Details::default(),
+        }
+    }
+
+    /// This lint with a fix which would resolve it.
+    pub fn with_fix(mut self, fix: Fix) -> Self {
+        self.fix = Some(fix);
+        self
+    }
+
+    /// This lint with secondary spans, notes, or help.
+    pub fn with_details(mut self, details: Details) -> Self {
+        self.details = details;
+        self
+    }
+
+    /// The kind of lint, i.e. which rule was broken.
+    pub fn kind(&self) -> LintKind {
+        self.kind
+    }
+
+    /// How severe the lint is, which is never [`Level::Allow`].
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    /// Where in the source the lint applies.
+    pub fn span(&self) -> &LintSpan {
+        &self.span
+    }
+
+    /// A fix which would resolve the lint, if one is known.
+    pub fn fix(&self) -> Option<&Fix> {
+        self.fix.as_ref()
+    }
+
+    /// Secondary spans, notes, and help for the lint.
+    pub fn details(&self) -> &Details {
+        &self.details
+    }
+}
+
+impl Label {
+    /// A label for the given span.
+    pub fn new(message: impl Into<String>, start: Cursor, len: usize) -> Self {
+        Self {
+            message: message.into(),
+            span: LintSpan { start, len },
+        }
+    }
+
+    /// How the span is involved, e.g. `first defined here`.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Where in the source the label applies.
+    pub fn span(&self) -> &LintSpan {
+        &self.span
+    }
+}
+
+impl LintSpan {
+    /// Position of the start of the span.
+    pub fn start(&self) -> Cursor {
+        self.start
+    }
+
+    /// Length of the span in bytes. Lints about something missing from the
+    /// source have zero-length spans at the place it should be.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the span is zero-length.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl LintKind {
+    /// Every kind of lint from the built-in rules.
+    pub const ALL: &'static [LintKind] = &[
+        LintKind::ImplicitBraces,
+        LintKind::MissingComma,
+        LintKind::TrailingComma,
+        LintKind::TrailingWhitespace,
+        LintKind::UnquotedKey,
+        LintKind::UnquotedValue,
+        LintKind::DuplicateKey,
+        LintKind::UnusedSuppression,
+    ];
+
+    /// The built-in kind of lint with the given name.
+    pub fn from_name(name: &str) -> Option<LintKind> {
+        LintKind::ALL
+            .iter()
+            .copied()
+            .find(|kind| kind.name() == name)
+    }
+
+    /// The built-in kind of lint with the given code.
+    pub fn from_code(code: &str) -> Option<LintKind> {
+        LintKind::ALL
+            .iter()
+            .copied()
+            .find(|kind| kind.code() == Some(code))
+    }
+
+    /// Stable code of a built-in rule, e.g. `HL003`. Codes are never reused,
+    /// even if their rule is removed.
+    pub fn code(&self) -> Option<&'static str> {
+        match self {
+            LintKind::ImplicitBraces => Some("HL001"),
+            LintKind::MissingComma => Some("HL002"),
+            LintKind::TrailingComma => Some("HL003"),
+            LintKind::TrailingWhitespace => Some("HL004"),
+            LintKind::UnquotedKey => Some("HL005"),
+            LintKind::UnquotedValue => Some("HL006"),
+            LintKind::DuplicateKey => Some("HL007"),
+            LintKind::UnusedSuppression => Some("HL008"),
+            LintKind::Custom { .. } => None,
+        }
+    }
+
+    /// Why the rule exists, with examples, if it's built in.
+    pub fn explain(&self) -> Option<Explanation> {
+        Explanation::of(*self)
+    }
+
+    /// Name of the rule in kebab-case, e.g. `trailing-comma`. Names are as
+    /// stable as codes.
+    pub fn name(&self) -> &'static str {
+        match self {
+            LintKind::ImplicitBraces => "implicit-braces",
+            LintKind::MissingComma => "missing-comma",
+            LintKind::TrailingComma => "trailing-comma",
+            LintKind::TrailingWhitespace => "trailing-whitespace",
+            LintKind::UnquotedKey => "unquoted-key",
+            LintKind::UnquotedValue => "unquoted-value",
+            LintKind::DuplicateKey => "duplicate-key",
+            LintKind::UnusedSuppression => "unused-suppression",
+            LintKind::Custom { rule, .. } => rule,
+        }
+    }
 }
 
 impl Display for LintKind {
@@ -36,241 +236,269 @@ impl Display for LintKind {
             LintKind::MissingComma => f.write_str("missing comma"),
             LintKind::TrailingComma => f.write_str("trailing comma"),
             LintKind::TrailingWhitespace => f.write_str("trailing whitespace"),
+            LintKind::UnquotedKey => f.write_str("unquoted key"),
+            LintKind::UnquotedValue => f.write_str("unquoted value"),
+            LintKind::DuplicateKey => f.write_str("duplicate key"),
+            LintKind::UnusedSuppression => f.write_str("unused suppression"),
+            LintKind::Custom { message, .. } => f.write_str(message),
         }
     }
 }
 
-#[derive(Clone, Debug, Default)]
-pub struct Linter {
-    config: Config,
+/// The state of linting a file, which rules report lints to.
+#[derive(Clone, Debug)]
+pub struct Linter<'a> {
+    config: Cow<'a, Config>,
+    input: &'a str,
     lints: Vec<Lint>,
 }
 
-impl Linter {
-    pub fn lint(config: Config, input: &str) -> Result<Vec<Lint>, ParseError> {
-        let mut linter = Linter {
-            config,
-            lints: Vec::new(),
-        };
+impl<'a> Linter<'a> {
+    /// Lint a file with the built-in rules, failing if it has any syntax
+    /// errors.
+    ///
+    /// The configuration can be given by value or by reference.
+    pub fn lint<'c>(
+        config: impl Into<Cow<'c, Config>>,
+        input: &'a str,
+    ) -> Result<Vec<Lint>, ParseError> {
+        Registry::default().lint(config, input)
+    }
 
-        let ast = Parser::parse(input)?;
-        linter.lint_root(&ast);
+    /// Lint whatever parts of a file could be parsed with the built-in rules,
+    /// returning every syntax error found alongside the lints.
+    ///
+    /// Lints on the same lines as syntax errors are left out, as they are
+    /// likely to be caused by the errors.
+    pub fn lint_resilient<'c>(
+        config: impl Into<Cow<'c, Config>>,
+        input: &'a str,
+    ) -> (Vec<Lint>, Vec<ParseError>) {
+        Registry::default().lint_resilient(config, input)
+    }
 
-        Ok(linter.lints)
+    /// Configuration the part of the file being checked is linted with,
+    /// including any scopes which apply to it.
+    pub fn config(&self) -> &Config {
+        &self.config
     }
 
-    fn lint_root(&mut self, map: &Map) {
-        self.lint_root_braces(map);
-        self.lint_map(map);
+    /// Contents of the file being linted.
+    pub fn input(&self) -> &'a str {
+        self.input
     }
 
-    fn lint_map(&mut self, map: &Map) {
-        self.lint_trailing_whitespace(&map.open_brace);
-        self.lint_trailing_whitespace(&map.close_brace);
+    /// Text of the given span in the input.
+    pub fn text(&self, span: &Span) -> &'a str {
+        let start = span.start.byte_offset;
+        &self.input[start..start + span.len]
+    }
 
-        for (i, map_member) in map.members.iter().enumerate() {
-            self.lint_map_member(map_member, i == map.members.len() - 1);
-        }
+    /// Report a lint found in the file.
+    pub fn report(&mut self, lint: Lint) {
+        self.lints.push(lint);
     }
 
-    fn lint_map_member(&mut self, map_member: &Node<MapMember>, last: bool) {
-        self.lint_trailing_whitespace(map_member);
-        self.lint_trailing_whitespace(&map_member.inner.comma);
-        self.lint_value(&map_member.inner.value);
+    /// Lint a file with the rules in `registry`.
+    fn run(
+        registry: &'a Registry,
+        config: &'a Config,
+        input: &'a str,
+    ) -> (Vec<Lint>, Vec<ParseError>) {
+        let mut walk = Walk {
+            registry,
+            base: config,
+            rules: Walk::rules(registry, config),
+            path: Vec::new(),
+            scopes: Vec::new(),
+            linter: Linter {
+                config: Cow::Borrowed(config),
+                input,
+                lints: Vec::new(),
+            },
+        };
+
+        let (ast, errors) = Parser::parse_resilient(input);
+        walk.root(&ast);
 
-        if last {
-            self.lint_trailing_comma(&map_member.inner.comma);
-        } else {
-            self.lint_missing_comma(&map_member.inner.comma);
-        }
-    }
+        let mut linter = walk.linter;
+        linter.config = Cow::Borrowed(config);
+        linter.suppress(registry, &ast);
 
-    fn lint_array(&mut self, array: &Array) {
-        for (i, array_member) in array.members.iter().enumerate() {
-            self.lint_array_member(array_member, i == array.members.len() - 1);
-        }
+        let mut lints = linter.lints;
+        lints.retain(|lint| {
+            let line = lint.span.start.line;
+            errors.iter().all(|error| error.got().start.line != line)
+        });
+
+        // Report lints in the order they appear, rather than the order the
+        // rules ran in.
+        lints.sort_by_key(|lint| {
+            let kind = lint.kind;
+            (lint.span.start.byte_offset, kind.code(), kind.name())
+        });
+
+        (lints, errors)
     }
 
-    fn lint_array_member(&mut self, array_member: &Node<ArrayMember>, last: bool) {
-        self.lint_trailing_whitespace(array_member);
-        self.lint_trailing_whitespace(&array_member.inner.comma);
-        self.lint_value(&array_member.inner.value);
+    /// Remove lints allowed by suppression comments, and lint any comments
+    /// which didn't allow anything.
+    fn suppress(&mut self, registry: &Registry, root: &Map) {
+        let mut suppressions = Suppression::find(self.input, root);
+        let unused = suppress::apply(&mut suppressions, &mut self.lints, registry, &self.config);
 
-        if last {
-            self.lint_trailing_comma(&array_member.inner.comma);
-        } else {
-            self.lint_missing_comma(&array_member.inner.comma);
+        let level = self.config.level(LintKind::UnusedSuppression);
+        if level != Level::Allow {
+            self.lints
+                .extend(unused.into_iter().map(|lint| Lint { level, ..lint }));
         }
     }
+}
 
-    fn lint_value(&mut self, value: &Value) {
-        let _value = match value {
-            Value::Map(map) => return self.lint_map(map),
-            Value::Array(array) => return self.lint_array(array),
-            Value::Value(value) => value,
-        };
+/// A walk through a document, calling each rule's hooks as it goes.
+struct Walk<'a> {
+    registry: &'a Registry,
+    /// Configuration for the whole document, before any scopes apply.
+    base: &'a Config,
+    /// The rules which aren't allowed where the walk is, and their levels.
+    rules: Vec<(&'a dyn Rule, Level)>,
+    /// Decoded keys and indices from the root to where the walk is.
+    path: Vec<String>,
+    /// Indices of the scopes which apply where the walk is.
+    scopes: Vec<usize>,
+    linter: Linter<'a>,
+}
+
+impl<'a> Walk<'a> {
+    /// The rules in `registry` which aren't allowed by `config`, and their
+    /// levels.
+    fn rules(registry: &'a Registry, config: &Config) -> Vec<(&'a dyn Rule, Level)> {
+        registry
+            .rules()
+            .map(|rule| (rule, rule.level(config)))
+            .filter(|(_, level)| *level != Level::Allow)
+            .collect()
     }
 
-    fn lint_trailing_whitespace<T>(&mut self, node: &Node<T>) {
-        if self.config.trailing_whitespace == AllowDeny::Allow {
+    /// Switch to the configuration of the scopes which apply at the current
+    /// path, if they've changed.
+    fn rescope(&mut self) {
+        let scopes: Vec<usize> = self
+            .base
+            .scopes
+            .iter()
+            .enumerate()
+            .filter(|(_, scope)| scope.matches(&self.path))
+            .map(|(i, _)| i)
+            .collect();
+        if scopes == self.scopes {
             return;
         }
 
-        let mut trailing_whitespace = |tokens: &Vec<Span>| {
-            // Span of the current run of whitespace we're looking at.
-            let mut whitespace = None;
-
-            // Scan tokens for whitespace followed by a newline.
-            for token in tokens {
-                match token.kind {
-                    // Whitespace starts or extends the span.
-                    TokenKind::Whitespace => {
-                        whitespace
-                            .get_or_insert(LintSpan {
-                                start: token.start,
-                                len: 0,
-                            })
-                            .len += token.len;
-                    }
-                    // New lines and EOLs publish a lint and reset the span.
-                    TokenKind::NewLine | TokenKind::Eof => {
-                        if let Some(span) = whitespace {
-                            self.lints.push(Lint {
-                                kind: LintKind::TrailingWhitespace,
-                                span,
-                            });
-                        }
-                        whitespace = None
-                    }
-                    // Anything else (comments) resets the span.
-                    _ => whitespace = None,
+        let config = match scopes.is_empty() {
+            true => Cow::Borrowed(self.base),
+            false => {
+                let mut config = self.base.clone();
+                for &i in &scopes {
+                    self.base.scopes[i].apply(&mut config);
                 }
+                Cow::Owned(config)
             }
         };
 
-        trailing_whitespace(&node.before);
-        trailing_whitespace(&node.after);
+        self.rules = Self::rules(self.registry, &config);
+        self.linter.config = config;
+        self.scopes = scopes;
     }
 
-    fn lint_root_braces(&mut self, map: &Map) {
-        match self.config.root_braces {
-            AllowDenyRequire::Deny => {
-                if let Some(ref brace) = map.open_brace.inner {
-                    self.lints.push(Lint {
-                        kind: LintKind::ImplicitBraces,
-                        span: LintSpan {
-                            start: brace.start,
-                            len: brace.len,
-                        },
-                    });
-                }
-            }
-            AllowDenyRequire::Require if map.open_brace.inner.is_none() => {
-                let cursor = map
-                    .open_brace
-                    .before
-                    .last()
-                    .map_or(Cursor::default(), |span| {
-                        let newline = span.kind == TokenKind::NewLine;
-                        Cursor {
-                            line: span.start.line + if newline { 1 } else { 0 },
-                            column: if newline {
-                                1
-                            } else {
-                                span.start.column + span.len
-                            },
-                            byte_offset: span.start.byte_offset + span.len,
-                        }
-                    });
+    /// Walk a member's contents at the path to it, then return to the parent.
+    fn member(&mut self, key: String, walk: impl FnOnce(&mut Self)) {
+        self.path.push(key);
+        self.rescope();
+        walk(self);
+        self.path.pop();
+        self.rescope();
+    }
 
-                self.lints.push(Lint {
-                    kind: LintKind::ImplicitBraces,
-                    span: LintSpan {
-                        start: cursor,
-                        len: 0,
-                    },
-                });
+    /// Call a hook of every rule, giving the lints it reports the rule's level.
+    fn check(&mut self, hook: impl Fn(&dyn Rule, &mut Linter<'a>)) {
+        for &(rule, level) in &self.rules {
+            let start = self.linter.lints.len();
+            hook(rule, &mut self.linter);
+
+            for lint in &mut self.linter.lints[start..] {
+                lint.level = level;
             }
-            _ => (),
         }
     }
 
-    fn lint_trailing_comma(&mut self, comma: &Node<Option<Span>>) {
-        if self.config.trailing_commas == AllowDenyRequire::Allow {
-            return;
-        }
+    fn root(&mut self, map: &Map) {
+        self.rescope();
+        self.check(|rule, linter| rule.check_root(linter, map));
+        self.map(map);
+    }
 
-        // If this comma site isn't followed by a new line, we don't treat it as trailing.
-        if !comma
-            .after
-            .iter()
-            .any(|span| span.kind == TokenKind::NewLine || span.kind == TokenKind::Eof)
-        {
-            return;
-        };
+    fn map(&mut self, map: &Map) {
+        self.trivia(&map.open_brace);
+        self.trivia(&map.close_brace);
+        self.check(|rule, linter| rule.check_map(linter, map));
 
-        let first = comma
-            .before
-            .iter()
-            .chain(comma.after.iter())
-            .next()
-            .expect("expected some space where comma is");
-
-        // Check for trailing commas.
-        match self.config.trailing_commas {
-            AllowDenyRequire::Deny => {
-                if let Some(ref node) = comma.inner {
-                    self.lints.push(Lint {
-                        kind: LintKind::TrailingComma,
-                        span: LintSpan {
-                            start: node.start,
-                            len: node.len,
-                        },
-                    })
-                }
-            }
-            AllowDenyRequire::Require if comma.inner.is_none() => self.lints.push(Lint {
-                kind: LintKind::TrailingComma,
-                span: LintSpan {
-                    start: comma.before.first().map_or(first.start, |span| span.start),
-                    len: 0,
-                },
-            }),
-            _ => (),
+        for (i, member) in map.members.iter().enumerate() {
+            let last = i == map.members.len() - 1;
+            let key = self.linter.text(&member.inner.key);
+            let key = hjson_parser::value::unquote(key).unwrap_or_else(|_| key.to_string());
+
+            self.member(key, |walk| {
+                walk.trivia(member);
+                walk.trivia(&member.inner.comma);
+                walk.check(|rule, linter| rule.check_map_member(linter, member, last));
+                walk.value(&member.inner.value, &member.inner.comma);
+            });
         }
     }
 
-    fn lint_missing_comma(&mut self, comma: &Node<Option<Span>>) {
-        if self.config.missing_commas == AllowDeny::Allow {
-            return;
-        }
+    fn array(&mut self, array: &Array) {
+        self.check(|rule, linter| rule.check_array(linter, array));
 
-        let first = comma
-            .before
-            .iter()
-            .chain(comma.after.iter())
-            .next()
-            .expect("expected some space where comma is");
+        for (i, member) in array.members.iter().enumerate() {
+            let last = i == array.members.len() - 1;
 
-        if comma.inner.is_none() {
-            self.lints.push(Lint {
-                kind: LintKind::MissingComma,
-                span: LintSpan {
-                    start: first.start,
-                    len: 0,
-                },
-            })
+            self.member(i.to_string(), |walk| {
+                walk.trivia(member);
+                walk.trivia(&member.inner.comma);
+                walk.check(|rule, linter| rule.check_array_member(linter, member, last));
+                walk.value(&member.inner.value, &member.inner.comma);
+            });
         }
     }
+
+    fn value(&mut self, value: &Value, comma: &Node<Option<Span>>) {
+        match value {
+            Value::Map(map) => self.map(map),
+            Value::Array(array) => self.array(array),
+            Value::Value(value) => {
+                self.check(|rule, linter| rule.check_value(linter, value, comma))
+            }
+        }
+    }
+
+    fn trivia<T>(&mut self, node: &Node<T>) {
+        self.check(|rule, linter| {
+            rule.check_trivia(linter, &node.before);
+            rule.check_trivia(linter, &node.after);
+        });
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::fix::Edit;
+    use indoc::indoc;
 
     #[test]
     fn allow_trailing_whitespace() {
-        let conf = Config {
+        let conf = &Config {
             trailing_whitespace: AllowDeny::Allow,
             ..Default::default()
         };
@@ -281,7 +509,7 @@ mod test {
 
     #[test]
     fn deny_trailing_whitespace() {
-        let conf = Config {
+        let conf = &Config {
             trailing_whitespace: AllowDeny::Deny,
             ..Default::default()
         };
@@ -298,6 +526,7 @@ mod test {
             Linter::lint(conf, "'foo': 3  \t").unwrap(),
             Vec::from([Lint {
                 kind: LintKind::TrailingWhitespace,
+                level: Level::Deny,
                 span: LintSpan {
                     start: Cursor {
                         line: 1,
@@ -305,7 +534,9 @@ mod test {
                         byte_offset: 8
                     },
                     len: 3,
-                }
+                },
+                fix: Some(Fix::delete(8, 3)),
+                details: Details::default(),
             }])
         );
         // Trailing whitespace terminated by new line.
@@ -313,6 +544,7 @@ mod test {
             Linter::lint(conf, "'foo': 3  \t\n'bar': 5").unwrap(),
             Vec::from([Lint {
                 kind: LintKind::TrailingWhitespace,
+                level: Level::Deny,
                 span: LintSpan {
                     start: Cursor {
                         line: 1,
@@ -320,7 +552,9 @@ mod test {
                         byte_offset: 8
                     },
                     len: 3,
-                }
+                },
+                fix: Some(Fix::delete(8, 3)),
+                details: Details::default(),
             }])
         );
         // Not trailing whitespace if it's closed by the map on the same line.
@@ -329,7 +563,7 @@ mod test {
 
     #[test]
     fn allow_root_braces() {
-        let conf = Config {
+        let conf = &Config {
             root_braces: AllowDenyRequire::Allow,
             ..Default::default()
         };
@@ -340,7 +574,7 @@ mod test {
 
     #[test]
     fn deny_root_braces() {
-        let conf = Config {
+        let conf = &Config {
             root_braces: AllowDenyRequire::Deny,
             ..Default::default()
         };
@@ -350,6 +584,7 @@ mod test {
             Linter::lint(conf, "{ 'foo': 3 }").unwrap(),
             Vec::from([Lint {
                 kind: LintKind::ImplicitBraces,
+                level: Level::Deny,
                 span: LintSpan {
                     start: Cursor {
                         line: 1,
@@ -357,14 +592,36 @@ mod test {
                         byte_offset: 0
                     },
                     len: 1,
-                }
+                },
+                fix: Some(Fix {
+                    edits: Vec::from([
+                        Edit {
+                            offset: 0,
+                            len: 1,
+                            text: String::new()
+                        },
+                        Edit {
+                            offset: 11,
+                            len: 1,
+                            text: String::new()
+                        }
+                    ])
+                }),
+                details: Details {
+                    labels: Vec::from([Label::new(
+                        "closing brace here",
+                        Cursor::new(1, 12, 11),
+                        1
+                    )]),
+                    ..Details::default()
+                },
             }])
         );
     }
 
     #[test]
     fn require_root_braces() {
-        let conf = Config {
+        let conf = &Config {
             root_braces: AllowDenyRequire::Require,
             ..Default::default()
         };
@@ -374,6 +631,7 @@ mod test {
             Linter::lint(conf, "'foo': 3").unwrap(),
             Vec::from([Lint {
                 kind: LintKind::ImplicitBraces,
+                level: Level::Deny,
                 span: LintSpan {
                     start: Cursor {
                         line: 1,
@@ -381,14 +639,29 @@ mod test {
                         byte_offset: 0
                     },
                     len: 0,
-                }
+                },
+                fix: Some(Fix {
+                    edits: Vec::from([
+                        Edit {
+                            offset: 0,
+                            len: 0,
+                            text: String::from("{\n")
+                        },
+                        Edit {
+                            offset: 8,
+                            len: 0,
+                            text: String::from("\n}")
+                        }
+                    ])
+                }),
+                details: Details::default(),
             }])
         );
     }
 
     #[test]
     fn allow_trailing_commas() {
-        let conf = Config {
+        let conf = &Config {
             trailing_commas: AllowDenyRequire::Allow,
             ..Default::default()
         };
@@ -411,7 +684,7 @@ mod test {
 
     #[test]
     fn deny_trailing_commas() {
-        let conf = Config {
+        let conf = &Config {
             trailing_commas: AllowDenyRequire::Deny,
             ..Default::default()
         };
@@ -437,6 +710,7 @@ mod test {
             Linter::lint(conf, "'foo': 3,").unwrap(),
             Vec::from([Lint {
                 kind: LintKind::TrailingComma,
+                level: Level::Deny,
                 span: LintSpan {
                     start: Cursor {
                         line: 1,
@@ -444,7 +718,9 @@ mod test {
                         byte_offset: 8,
                     },
                     len: 1
-                }
+                },
+                fix: Some(Fix::delete(8, 1)),
+                details: Details::default(),
             }])
         );
         // Two map members, only one comma is trailing.
@@ -452,6 +728,7 @@ mod test {
             Linter::lint(conf, "'foo': 3,\n'bar': 5,").unwrap(),
             Vec::from([Lint {
                 kind: LintKind::TrailingComma,
+                level: Level::Deny,
                 span: LintSpan {
                     start: Cursor {
                         line: 2,
@@ -459,7 +736,9 @@ mod test {
                         byte_offset: 18,
                     },
                     len: 1
-                }
+                },
+                fix: Some(Fix::delete(18, 1)),
+                details: Details::default(),
             }])
         );
 
@@ -468,6 +747,7 @@ mod test {
             Linter::lint(conf, "'a': [\n3,\n]").unwrap(),
             Vec::from([Lint {
                 kind: LintKind::TrailingComma,
+                level: Level::Deny,
                 span: LintSpan {
                     start: Cursor {
                         line: 2,
@@ -475,7 +755,9 @@ mod test {
                         byte_offset: 8,
                     },
                     len: 1
-                }
+                },
+                fix: Some(Fix::delete(8, 1)),
+                details: Details::default(),
             }])
         );
         // Two array members, only one comma is trailing.
@@ -483,6 +765,7 @@ mod test {
             Linter::lint(conf, "'a': [\n3,\n5,\n]").unwrap(),
             Vec::from([Lint {
                 kind: LintKind::TrailingComma,
+                level: Level::Deny,
                 span: LintSpan {
                     start: Cursor {
                         line: 3,
@@ -490,7 +773,9 @@ mod test {
                         byte_offset: 11,
                     },
                     len: 1
-                }
+                },
+                fix: Some(Fix::delete(11, 1)),
+                details: Details::default(),
             }])
         );
 
@@ -502,7 +787,7 @@ mod test {
 
     #[test]
     fn require_trailing_commas() {
-        let conf = Config {
+        let conf = &Config {
             trailing_commas: AllowDenyRequire::Require,
             ..Default::default()
         };
@@ -517,6 +802,7 @@ mod test {
 
         let lints = Vec::from([Lint {
             kind: LintKind::TrailingComma,
+            level: Level::Deny,
             span: LintSpan {
                 start: Cursor {
                     line: 1,
@@ -525,6 +811,8 @@ mod test {
                 },
                 len: 0,
             },
+            fix: Some(Fix::insert(8, ",")),
+            details: Details::default(),
         }]);
         // One map member, trailing comma not provided.
         assert_eq!(Linter::lint(conf, "'foo': 3").unwrap(), lints);
@@ -536,6 +824,7 @@ mod test {
 
         let lints = Vec::from([Lint {
             kind: LintKind::TrailingComma,
+            level: Level::Deny,
             span: LintSpan {
                 start: Cursor {
                     line: 2,
@@ -544,6 +833,8 @@ mod test {
                 },
                 len: 0,
             },
+            fix: Some(Fix::insert(14, ",")),
+            details: Details::default(),
         }]);
         // Two map members, trailing comma not provided.
         assert_eq!(Linter::lint(conf, "'x': 3,\n'y': 5").unwrap(), lints);
@@ -552,6 +843,7 @@ mod test {
 
         let lints = Vec::from([Lint {
             kind: LintKind::TrailingComma,
+            level: Level::Deny,
             span: LintSpan {
                 start: Cursor {
                     line: 2,
@@ -560,6 +852,8 @@ mod test {
                 },
                 len: 0,
             },
+            fix: Some(Fix::insert(14, ",")),
+            details: Details::default(),
         }]);
         // Two map members, trailing comma not provided.
         assert_eq!(Linter::lint(conf, "'a': [ 1234,\n5\n],").unwrap(), lints);
@@ -573,7 +867,7 @@ mod test {
 
     #[test]
     fn allow_missing_commas() {
-        let conf = Config {
+        let conf = &Config {
             missing_commas: AllowDeny::Allow,
             ..Default::default()
         };
@@ -585,7 +879,7 @@ mod test {
 
     #[test]
     fn deny_missing_commas() {
-        let conf = Config {
+        let conf = &Config {
             missing_commas: AllowDeny::Deny,
             ..Default::default()
         };
@@ -596,6 +890,7 @@ mod test {
 
         let lints = Vec::from([Lint {
             kind: LintKind::MissingComma,
+            level: Level::Deny,
             span: LintSpan {
                 start: Cursor {
                     line: 1,
@@ -604,9 +899,372 @@ mod test {
                 },
                 len: 0,
             },
+            fix: Some(Fix::insert(6, ",")),
+            details: Details::default(),
         }]);
         // Missing comma (implicit by newline)
         assert_eq!(Linter::lint(conf, "'x': 3\n'y': 5").unwrap(), lints);
         assert_eq!(Linter::lint(conf, "'x': 3 \t\n'y': 5").unwrap(), lints);
     }
+
+    #[test]
+    fn allow_unquoted_keys() {
+        let conf = &Config {
+            unquoted_keys: AllowDenyRequire::Allow,
+            ..Default::default()
+        };
+
+        assert!(Linter::lint(conf, "foo: 3").unwrap().is_empty());
+        assert!(Linter::lint(conf, "'foo': 3").unwrap().is_empty());
+        assert!(Linter::lint(conf, "\"foo\": 3").unwrap().is_empty());
+    }
+
+    #[test]
+    fn deny_unquoted_keys() {
+        let conf = &Config {
+            unquoted_keys: AllowDenyRequire::Deny,
+            ..Default::default()
+        };
+
+        assert!(Linter::lint(conf, "'foo': 3").unwrap().is_empty());
+        assert!(Linter::lint(conf, "\"foo\": 3").unwrap().is_empty());
+        assert!(Linter::lint(conf, "'a': { 'b': 'c' }").unwrap().is_empty());
+
+        assert_eq!(
+            Linter::lint(conf, "'a': { foo: 3 }").unwrap(),
+            Vec::from([Lint {
+                kind: LintKind::UnquotedKey,
+                level: Level::Deny,
+                span: LintSpan {
+                    start: Cursor {
+                        line: 1,
+                        column: 8,
+                        byte_offset: 7,
+                    },
+                    len: 3,
+                },
+                fix: Some(Fix::replace(7, 3, "\"foo\"")),
+                details: Details::default(),
+            }])
+        );
+    }
+
+    #[test]
+    fn require_unquoted_keys() {
+        let conf = &Config {
+            unquoted_keys: AllowDenyRequire::Require,
+            ..Default::default()
+        };
+
+        assert!(Linter::lint(conf, "foo: 3").unwrap().is_empty());
+        assert!(Linter::lint(conf, "foo.bar-baz: 3").unwrap().is_empty());
+
+        // Keys which would change meaning or fail to parse without quotes.
+        assert!(Linter::lint(conf, "'': 3").unwrap().is_empty());
+        assert!(Linter::lint(conf, "'foo bar': 3").unwrap().is_empty());
+        assert!(Linter::lint(conf, "'a:b': 3").unwrap().is_empty());
+        assert!(Linter::lint(conf, "'a,b': 3").unwrap().is_empty());
+        assert!(Linter::lint(conf, "'{a}': 3").unwrap().is_empty());
+        assert!(Linter::lint(conf, "'#a': 3").unwrap().is_empty());
+        assert!(Linter::lint(conf, "'//a': 3").unwrap().is_empty());
+        assert!(Linter::lint(conf, "\"'a\": 3").unwrap().is_empty());
+        assert!(Linter::lint(conf, r"'a\nb': 3").unwrap().is_empty());
+
+        let lints = Vec::from([Lint {
+            kind: LintKind::UnquotedKey,
+            level: Level::Deny,
+            span: LintSpan {
+                start: Cursor {
+                    line: 1,
+                    column: 1,
+                    byte_offset: 0,
+                },
+                len: 5,
+            },
+            fix: Some(Fix::replace(0, 5, "foo")),
+            details: Details::default(),
+        }]);
+        assert_eq!(Linter::lint(conf, "'foo': 3").unwrap(), lints);
+        assert_eq!(Linter::lint(conf, "\"foo\": 3").unwrap(), lints);
+    }
+
+    #[test]
+    fn allow_unquoted_values() {
+        let conf = &Config {
+            unquoted_values: AllowDenyRequire::Allow,
+            ..Default::default()
+        };
+
+        assert!(Linter::lint(conf, "'a': foo").unwrap().is_empty());
+        assert!(Linter::lint(conf, "'a': 'foo'").unwrap().is_empty());
+        assert!(Linter::lint(conf, "'a': [ foo\n]").unwrap().is_empty());
+    }
+
+    #[test]
+    fn deny_unquoted_values() {
+        let conf = &Config {
+            unquoted_values: AllowDenyRequire::Deny,
+            ..Default::default()
+        };
+
+        assert!(Linter::lint(conf, "'a': 'foo'").unwrap().is_empty());
+        assert!(Linter::lint(conf, "'a': \"foo\"").unwrap().is_empty());
+        assert!(Linter::lint(conf, "'a': '''foo'''").unwrap().is_empty());
+        assert!(Linter::lint(conf, "'a': 3").unwrap().is_empty());
+        assert!(Linter::lint(conf, "'a': true").unwrap().is_empty());
+        assert!(Linter::lint(conf, "'a': null").unwrap().is_empty());
+
+        assert_eq!(
+            Linter::lint(conf, "'a': {\n  'b': foo bar\n}").unwrap(),
+            Vec::from([Lint {
+                kind: LintKind::UnquotedValue,
+                level: Level::Deny,
+                span: LintSpan {
+                    start: Cursor {
+                        line: 2,
+                        column: 8,
+                        byte_offset: 14,
+                    },
+                    len: 7,
+                },
+                fix: Some(Fix::replace(14, 7, "\"foo bar\"")),
+                details: Details::default(),
+            }])
+        );
+    }
+
+    #[test]
+    fn require_unquoted_values() {
+        let conf = &Config {
+            unquoted_values: AllowDenyRequire::Require,
+            ..Default::default()
+        };
+
+        assert!(Linter::lint(conf, "'a': foo").unwrap().is_empty());
+        assert!(Linter::lint(conf, "'a': 20 apples").unwrap().is_empty());
+
+        // Values which would change meaning or fail to parse without quotes.
+        assert!(Linter::lint(conf, "'a': ''").unwrap().is_empty());
+        assert!(Linter::lint(conf, "'a': '3'").unwrap().is_empty());
+        assert!(Linter::lint(conf, "'a': 'true'").unwrap().is_empty());
+        assert!(Linter::lint(conf, "'a': 'null'").unwrap().is_empty());
+        assert!(Linter::lint(conf, "'a': ' foo'").unwrap().is_empty());
+        assert!(Linter::lint(conf, "'a': 'foo '").unwrap().is_empty());
+        assert!(Linter::lint(conf, "'a': '[foo]'").unwrap().is_empty());
+        assert!(Linter::lint(conf, "'a': '# foo'").unwrap().is_empty());
+        assert!(Linter::lint(conf, r"'a': 'foo\tbar'").unwrap().is_empty());
+        assert!(Linter::lint(conf, "'a': '''foo'''").unwrap().is_empty());
+
+        // Values which would swallow what follows them on the line.
+        assert!(Linter::lint(conf, "'a': 'foo',").unwrap().is_empty());
+        assert!(Linter::lint(conf, "'a': 'foo' # comment")
+            .unwrap()
+            .is_empty());
+        assert!(Linter::lint(conf, "{ 'a': 'foo' }").unwrap().is_empty());
+        assert!(Linter::lint(conf, "'a': [ 'foo' ]").unwrap().is_empty());
+
+        let lints = Vec::from([Lint {
+            kind: LintKind::UnquotedValue,
+            level: Level::Deny,
+            span: LintSpan {
+                start: Cursor {
+                    line: 1,
+                    column: 6,
+                    byte_offset: 5,
+                },
+                len: 9,
+            },
+            fix: Some(Fix::replace(5, 9, "foo bar")),
+            details: Details::default(),
+        }]);
+        assert_eq!(Linter::lint(conf, "'a': 'foo bar'").unwrap(), lints);
+        assert_eq!(Linter::lint(conf, "'a': \"foo bar\" \t\n").unwrap(), lints);
+    }
+
+    #[test]
+    fn duplicate_keys() {
+        let input = "a: 1\nb: { a: 2 }\n'a': 3\n\"\\u0061\": 4\n";
+
+        // Keys are only compared within the same map.
+        assert_eq!(Linter::lint(Config::default(), input).unwrap(), Vec::new());
+
+        let conf = &Config {
+            duplicate_keys: AllowDeny::Deny,
+            ..Default::default()
+        };
+        let details = Details {
+            labels: Vec::from([Label::new("first defined here", Cursor::new(1, 1, 0), 1)]),
+            notes: Vec::from([String::from(
+                "parsers disagree on which value to use for a repeated key",
+            )]),
+            help: Some(String::from("remove or rename one of the keys")),
+        };
+        assert_eq!(
+            Linter::lint(conf, input).unwrap(),
+            [
+                Lint {
+                    kind: LintKind::DuplicateKey,
+                    level: Level::Deny,
+                    span: LintSpan {
+                        start: Cursor::new(3, 1, 17),
+                        len: 3,
+                    },
+                    fix: None,
+                    details: details.clone(),
+                },
+                Lint {
+                    kind: LintKind::DuplicateKey,
+                    level: Level::Deny,
+                    span: LintSpan {
+                        start: Cursor::new(4, 1, 24),
+                        len: 8,
+                    },
+                    fix: None,
+                    details,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn levels() {
+        let mut conf = Config::default();
+        conf.set_level("missing-comma", Level::Warn);
+        conf.set_level("trailing-comma", Level::Forbid);
+        conf.set_level("unused-suppression", Level::Warn);
+
+        let input = "a: 1\nb: '2', # hjson-lint: allow(trailing-comma)\n";
+        let lints = Linter::lint(conf, input).unwrap();
+        let levels: Vec<_> = lints
+            .iter()
+            .map(|lint| (lint.kind(), lint.level(), lint.span().start().line))
+            .collect();
+
+        // Forbidden lints can't be suppressed.
+        assert_eq!(
+            levels,
+            [
+                (LintKind::MissingComma, Level::Warn, 1),
+                (LintKind::TrailingComma, Level::Forbid, 2),
+                (LintKind::UnusedSuppression, Level::Warn, 2),
+            ]
+        );
+        assert_eq!(
+            lints[2].details().notes,
+            ["`trailing-comma` is forbidden, so can't be allowed"]
+        );
+    }
+
+    #[test]
+    fn codes() {
+        let mut codes: Vec<_> = LintKind::ALL.iter().map(|kind| kind.code()).collect();
+        codes.sort();
+        codes.dedup();
+        assert_eq!(codes.len(), LintKind::ALL.len());
+
+        for &kind in LintKind::ALL {
+            assert_eq!(LintKind::from_code(kind.code().unwrap()), Some(kind));
+            assert_eq!(LintKind::from_name(kind.name()), Some(kind));
+        }
+        assert_eq!(LintKind::from_code("HL000"), None);
+    }
+
+    #[test]
+    fn scopes() {
+        let conf = Config::parse(indoc! {r#"
+            extends: strict
+            root_braces: allow
+            scopes: {
+              "/registers/*/fields/*/desc": {
+                unquoted_values: allow
+                levels: { unquoted-key: "warn" }
+              }
+              "/registers/1": { unquoted_keys: "allow" }
+            }
+        "#})
+        .unwrap();
+
+        let input = indoc! {r#"
+            "registers": [
+              {
+                "fields": {
+                  "a": { desc: some text
+                         "name": "a" }
+                }
+                "desc": text
+              }
+              { "fields": { "b": { "desc": "text", name: "b" } } }
+            ]
+        "#};
+        let lints: Vec<_> = Linter::lint(&conf, input)
+            .unwrap()
+            .iter()
+            .filter(|lint| lint.kind() != LintKind::MissingComma)
+            .map(|lint| (lint.kind(), lint.level(), lint.span().start().line))
+            .collect();
+
+        // Scopes apply to the member whose value they match, including its
+        // key, and to everything inside it.
+        assert_eq!(
+            lints,
+            [
+                (LintKind::UnquotedKey, Level::Warn, 4),
+                (LintKind::UnquotedValue, Level::Deny, 7),
+            ]
+        );
+    }
+
+    #[test]
+    fn lint_with_syntax_errors() {
+        let conf = Config::strict();
+        let input = "{\n  'a': 1,\n  b 2  \n  'c': 3, \n}";
+
+        assert!(Linter::lint(&conf, input).is_err());
+
+        // The trailing whitespace after `b 2` is on the same line as the
+        // syntax error, so isn't reported.
+        let (lints, errors) = Linter::lint_resilient(&conf, input);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            lints,
+            [
+                Lint {
+                    kind: LintKind::TrailingComma,
+                    level: Level::Deny,
+                    span: LintSpan {
+                        start: Cursor::new(4, 9, 28),
+                        len: 1,
+                    },
+                    fix: Some(Fix::delete(28, 1)),
+                    details: Details::default(),
+                },
+                Lint {
+                    kind: LintKind::TrailingWhitespace,
+                    level: Level::Deny,
+                    span: LintSpan {
+                        start: Cursor::new(4, 10, 29),
+                        len: 1,
+                    },
+                    fix: Some(Fix::delete(29, 1)),
+                    details: Details::default(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn lint_without_whitespace() {
+        // Broken arrays lower to comma sites with no trivia around them.
+        for input in ["a:[null''", "[1'a'2", "{a:[null''}"] {
+            let (_, errors) = Linter::lint_resilient(Config::strict(), input);
+            assert!(!errors.is_empty(), "{input}");
+        }
+
+        let (lints, errors) = Linter::lint_resilient(Config::strict(), "a:[null''");
+        assert_eq!(
+            errors[0].to_string(),
+            "1:8: expected comma or new-line, got single-quoted string"
+        );
+        assert_eq!(lints, []);
+    }
 }
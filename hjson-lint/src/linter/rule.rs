@@ -0,0 +1,471 @@
+//! Linting rules, and the [`Registry`] of rules the linter applies.
+//!
+//! Each rule implements [`Rule`], and is called by the linter as it walks the
+//! document with every map, member, array, value, and run of trivia it finds.
+//! Rules only implement the hooks they need, and report lints to the
+//! [`Linter`] they're given:
+//!
+//! ```
+//! use hjson_lint::lexer::{Span, TokenKind};
+//! use hjson_lint::linter::{Level, Lint, LintKind, Linter, Registry, Rule};
+//!
+//! /// Deny comments mentioning `TODO`.
+//! struct NoTodo;
+//!
+//! impl Rule for NoTodo {
+//!     fn name(&self) -> &'static str {
+//!         "no-todo"
+//!     }
+//!
+//!     fn description(&self) -> &'static str {
+//!         "Comments about unfinished work."
+//!     }
+//!
+//!     fn default_level(&self) -> Level {
+//!         Level::Deny
+//!     }
+//!
+//!     fn check_trivia(&self, linter: &mut Linter, trivia: &[Span]) {
+//!         for span in trivia {
+//!             if span.kind != TokenKind::Whitespace && linter.text(span).contains("TODO") {
+//!                 let kind = LintKind::Custom {
+//!                     rule: self.name(),
+//!                     message: "unfinished work",
+//!                 };
+//!                 linter.report(Lint::new(kind, span.start, span.len));
+//!             }
+//!         }
+//!     }
+//! }
+//!
+//! let mut registry = Registry::default();
+//! registry.register(NoTodo);
+//!
+//! let lints = registry.lint(&Default::default(), "# TODO: more\na: 1").unwrap();
+//! assert_eq!(lints[0].kind().name(), "no-todo");
+//! ```
+
+use std::borrow::Cow;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use super::config::{AllowDeny, AllowDenyRequire, Config};
+use super::{builtin, Lint, Linter};
+use crate::lexer::Span;
+use crate::parser::ast::{Array, ArrayMember, Map, MapMember, Node};
+use crate::parser::ParseError;
+
+/// How severely a rule is applied, from least to most severe.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    /// The rule isn't checked.
+    Allow,
+    /// Breaking the rule is reported, but doesn't fail linting.
+    Warn,
+    /// Breaking the rule is reported and fails linting.
+    Deny,
+    /// Like [`Level::Deny`], but the rule can't be allowed by later settings
+    /// or by suppression comments.
+    Forbid,
+}
+
+impl Level {
+    /// Name of the level, as written in configuration, e.g. `warn`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Level::Allow => "allow",
+            Level::Warn => "warn",
+            Level::Deny => "deny",
+            Level::Forbid => "forbid",
+        }
+    }
+}
+
+impl Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Level {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "allow" => Ok(Level::Allow),
+            "warn" => Ok(Level::Warn),
+            "deny" => Ok(Level::Deny),
+            "forbid" => Ok(Level::Forbid),
+            _ => Err(()),
+        }
+    }
+}
+
+impl From<AllowDeny> for Level {
+    fn from(value: AllowDeny) -> Self {
+        match value {
+            AllowDeny::Allow => Level::Allow,
+            AllowDeny::Deny => Level::Deny,
+        }
+    }
+}
+
+impl From<AllowDenyRequire> for Level {
+    /// Rules which can be required report lints either way.
+    fn from(value: AllowDenyRequire) -> Self {
+        match value {
+            AllowDenyRequire::Allow => Level::Allow,
+            AllowDenyRequire::Deny | AllowDenyRequire::Require => Level::Deny,
+        }
+    }
+}
+
+/// A linting rule.
+///
+/// Every hook does nothing by default. Hooks are called in document order,
+/// with containers before their contents.
+pub trait Rule {
+    /// Name of the rule in kebab-case, e.g. `trailing-comma`, which is used
+    /// to refer to it in suppression comments and reports. This must not
+    /// change once published.
+    fn name(&self) -> &'static str;
+
+    /// One sentence describing what the rule looks for.
+    fn description(&self) -> &'static str;
+
+    /// Level the rule is applied at unless configured otherwise.
+    fn default_level(&self) -> Level;
+
+    /// Level the rule is applied at with the given configuration. Rules
+    /// which are allowed aren't called at all.
+    fn level(&self, config: &Config) -> Level {
+        config
+            .levels
+            .get(self.name())
+            .copied()
+            .unwrap_or_else(|| self.default_level())
+    }
+
+    /// Check the root map of the document, before it's checked as a map.
+    fn check_root(&self, _linter: &mut Linter, _root: &Map) {}
+
+    /// Check a map, including the root map, before any of its members.
+    fn check_map(&self, _linter: &mut Linter, _map: &Map) {}
+
+    /// Check a member of a map, before its value. `last` is whether it's the
+    /// final member of the map.
+    fn check_map_member(&self, _linter: &mut Linter, _member: &Node<MapMember>, _last: bool) {}
+
+    /// Check an array, before any of its members.
+    fn check_array(&self, _linter: &mut Linter, _array: &Array) {}
+
+    /// Check a member of an array, before its value. `last` is whether it's
+    /// the final member of the array.
+    fn check_array_member(&self, _linter: &mut Linter, _member: &Node<ArrayMember>, _last: bool) {}
+
+    /// Check a value which isn't a map or array, along with the comma site
+    /// which follows it.
+    fn check_value(&self, _linter: &mut Linter, _value: &Span, _comma: &Node<Option<Span>>) {}
+
+    /// Check a run of whitespace and comments. Every member, comma site, and
+    /// map brace has a run before and after it, which may be empty.
+    fn check_trivia(&self, _linter: &mut Linter, _trivia: &[Span]) {}
+}
+
+/// The set of rules the linter applies.
+///
+/// The default registry has every built-in rule, and further rules can be
+/// [registered](Registry::register) alongside them.
+pub struct Registry {
+    rules: Vec<Box<dyn Rule>>,
+}
+
+impl Registry {
+    /// A registry without any rules.
+    pub fn new() -> Self {
+        Self { rules: Vec::new() }
+    }
+
+    /// Add a rule to the registry.
+    ///
+    /// # Panics
+    ///
+    /// If a rule with the same name is already registered.
+    pub fn register(&mut self, rule: impl Rule + 'static) -> &mut Self {
+        assert!(
+            self.get(rule.name()).is_none(),
+            "rule `{}` is already registered",
+            rule.name()
+        );
+
+        self.rules.push(Box::new(rule));
+        self
+    }
+
+    /// The rule with the given name, if there is one.
+    pub fn get(&self, name: &str) -> Option<&dyn Rule> {
+        self.rules().find(|rule| rule.name() == name)
+    }
+
+    /// Every rule, in the order they were registered.
+    pub fn rules(&self) -> impl Iterator<Item = &dyn Rule> {
+        self.rules.iter().map(|rule| rule.as_ref())
+    }
+
+    /// Lint a file with these rules, failing if it has any syntax errors.
+    pub fn lint<'c>(
+        &self,
+        config: impl Into<Cow<'c, Config>>,
+        input: &str,
+    ) -> Result<Vec<Lint>, ParseError> {
+        let (lints, mut errors) = self.lint_resilient(config, input);
+
+        match errors.is_empty() {
+            true => Ok(lints),
+            false => Err(errors.remove(0)),
+        }
+    }
+
+    /// Lint whatever parts of a file could be parsed with these rules,
+    /// returning every syntax error found alongside the lints.
+    ///
+    /// Lints on the same lines as syntax errors are left out, as they are
+    /// likely to be caused by the errors.
+    pub fn lint_resilient<'c>(
+        &self,
+        config: impl Into<Cow<'c, Config>>,
+        input: &str,
+    ) -> (Vec<Lint>, Vec<ParseError>) {
+        Linter::run(self, &config.into(), input)
+    }
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        let mut registry = Self::new();
+        registry
+            .register(builtin::ImplicitBraces)
+            .register(builtin::MissingComma)
+            .register(builtin::TrailingComma)
+            .register(builtin::TrailingWhitespace)
+            .register(builtin::UnquotedKey)
+            .register(builtin::UnquotedValue)
+            .register(builtin::DuplicateKey);
+        registry
+    }
+}
+
+impl fmt::Debug for Registry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.rules().map(|rule| rule.name()))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::lexer::{Cursor, TokenKind};
+    use crate::linter::LintKind;
+    use indoc::indoc;
+
+    /// Deny arrays with more than two members.
+    struct ShortArrays;
+
+    impl Rule for ShortArrays {
+        fn name(&self) -> &'static str {
+            "short-arrays"
+        }
+
+        fn description(&self) -> &'static str {
+            "Arrays with more than two members."
+        }
+
+        fn default_level(&self) -> Level {
+            Level::Deny
+        }
+
+        fn check_array(&self, linter: &mut Linter, array: &Array) {
+            if array.members.len() > 2 {
+                let bracket = &array.open_bracket.inner;
+                let kind = LintKind::Custom {
+                    rule: self.name(),
+                    message: "long array",
+                };
+                linter.report(Lint::new(kind, bracket.start, bracket.len));
+            }
+        }
+    }
+
+    /// Deny `null`, but only when configured to deny unquoted values.
+    struct NoNull;
+
+    impl Rule for NoNull {
+        fn name(&self) -> &'static str {
+            "no-null"
+        }
+
+        fn description(&self) -> &'static str {
+            "Null values."
+        }
+
+        fn default_level(&self) -> Level {
+            Level::Allow
+        }
+
+        fn level(&self, config: &Config) -> Level {
+            config.unquoted_values.into()
+        }
+
+        fn check_value(&self, linter: &mut Linter, value: &Span, _comma: &Node<Option<Span>>) {
+            if value.kind == TokenKind::Null {
+                let kind = LintKind::Custom {
+                    rule: self.name(),
+                    message: "null value",
+                };
+                linter.report(Lint::new(kind, value.start, value.len));
+            }
+        }
+    }
+
+    /// Deny files which don't end with a comment, reporting at the end.
+    struct EndComment;
+
+    impl Rule for EndComment {
+        fn name(&self) -> &'static str {
+            "end-comment"
+        }
+
+        fn description(&self) -> &'static str {
+            "Files without a comment at the end."
+        }
+
+        fn default_level(&self) -> Level {
+            Level::Deny
+        }
+
+        fn check_root(&self, linter: &mut Linter, _map: &Map) {
+            let input = linter.input();
+            if !input.trim_end().ends_with("end") {
+                let kind = LintKind::Custom {
+                    rule: self.name(),
+                    message: "no end comment",
+                };
+                let line = input.lines().count() + 1;
+                linter.report(Lint::new(kind, Cursor::new(line, 1, input.len()), 0));
+            }
+        }
+    }
+
+    #[test]
+    fn position_order() {
+        let mut registry = Registry::new();
+        registry.register(EndComment).register(NoNull);
+
+        // The root is checked before the values in it, but lints are sorted
+        // by where they start.
+        let names: Vec<_> = registry
+            .lint(Config::strict(), "a: null\nb: null\n")
+            .unwrap()
+            .iter()
+            .map(|lint| (lint.kind().name(), lint.span().start().line))
+            .collect();
+        assert_eq!(names, [("no-null", 1), ("no-null", 2), ("end-comment", 3)]);
+    }
+
+    #[test]
+    fn custom_rules() {
+        let input = indoc! {"
+            a: [1, 2, 3]
+            b: null
+            # hjson-lint: allow(short-arrays)
+            c: [[4, 5, 6]]
+        "};
+
+        let mut registry = Registry::new();
+        registry.register(ShortArrays).register(NoNull);
+
+        let lints = registry.lint(Config::default(), input).unwrap();
+        assert_eq!(
+            lints,
+            [Lint::new(
+                LintKind::Custom {
+                    rule: "short-arrays",
+                    message: "long array"
+                },
+                Cursor::new(1, 4, 3),
+                1
+            )]
+        );
+        assert_eq!(lints[0].kind().to_string(), "long array");
+
+        let config = Config::strict();
+        let names: Vec<_> = registry
+            .lint(&config, input)
+            .unwrap()
+            .iter()
+            .map(|lint| (lint.kind().name(), lint.span().start().line))
+            .collect();
+        assert_eq!(names, [("short-arrays", 1), ("no-null", 2)]);
+
+        // Built-in rules are only applied by registries which have them.
+        let lints = Registry::default().lint(&config, input).unwrap();
+        assert!(lints
+            .iter()
+            .any(|lint| lint.kind() == LintKind::ImplicitBraces));
+        assert!(lints
+            .iter()
+            .all(|lint| !matches!(lint.kind(), LintKind::Custom { .. })));
+    }
+
+    #[test]
+    fn builtin_rules() {
+        let registry = Registry::default();
+        let names: Vec<_> = registry.rules().map(|rule| rule.name()).collect();
+
+        // Every kind of lint except unused suppressions comes from a rule.
+        let kinds: Vec<_> = LintKind::ALL
+            .iter()
+            .filter(|kind| **kind != LintKind::UnusedSuppression)
+            .map(|kind| kind.name())
+            .collect();
+        assert_eq!(names, kinds);
+
+        for rule in registry.rules() {
+            assert_eq!(rule.default_level(), Level::Allow, "{}", rule.name());
+            assert_eq!(
+                rule.level(&Config::strict()),
+                Level::Deny,
+                "{}",
+                rule.name()
+            );
+        }
+
+        let rule = registry.get("trailing-whitespace").unwrap();
+        assert_eq!(rule.description(), "Whitespace at the end of a line.");
+        assert!(registry.get("unknown-rule").is_none());
+    }
+
+    #[test]
+    fn compact_input() {
+        // Comma sites without any whitespace around them.
+        let registry = Registry::default();
+        for config in [Config::default(), Config::strict()] {
+            assert_eq!(registry.lint(&config, r#"{"a":1,"b":[1,2]}"#).unwrap(), []);
+        }
+
+        let require = Config {
+            trailing_commas: AllowDenyRequire::Require,
+            ..Config::strict()
+        };
+        let lints = registry.lint(&require, r#"{"a":[1,2]}"#).unwrap();
+        assert_eq!(lints, []);
+    }
+
+    #[test]
+    #[should_panic = "rule `trailing-comma` is already registered"]
+    fn duplicate_rules() {
+        Registry::default().register(crate::linter::builtin::TrailingComma);
+    }
+}
@@ -0,0 +1,133 @@
+//! Rules scoped to parts of a document by their key paths.
+//!
+//! A scope's pattern is written like a JSON pointer, such as
+//! `/registers/*/fields/*/desc`. Each segment matches a map key or an array
+//! index, and `*` matches any one of them. `~1` and `~0` in a segment stand
+//! for `/` and `~`, and the empty pattern matches the whole document.
+//!
+//! A scope applies to the value at each matching path and to everything in
+//! it, including the keys of the members it's the value of.
+
+use super::config::{Config, Setting};
+
+/// Rules which apply to the parts of a document at matching key paths.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Scope {
+    pattern: String,
+    segments: Vec<Segment>,
+    settings: Vec<Setting>,
+}
+
+/// A segment of a scope's pattern.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Segment {
+    /// A map key, or an array index written in decimal.
+    Key(String),
+    /// `*`, which matches any key or index.
+    Any,
+}
+
+impl Scope {
+    /// A scope applying `settings` to paths matching `pattern`.
+    pub(super) fn new(pattern: &str, settings: Vec<Setting>) -> Result<Self, &'static str> {
+        let segments = match pattern.strip_prefix('/') {
+            Some(rest) => rest
+                .split('/')
+                .map(Segment::parse)
+                .collect::<Result<_, _>>()?,
+            None if pattern.is_empty() => Vec::new(),
+            None => return Err("patterns must be empty or start with `/`"),
+        };
+
+        Ok(Self {
+            pattern: pattern.to_string(),
+            segments,
+            settings,
+        })
+    }
+
+    /// Pattern of the paths the scope applies to, as written.
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Whether the scope applies to the value at `path`, a list of decoded
+    /// map keys and array indices from the root.
+    pub fn matches<S: AsRef<str>>(&self, path: &[S]) -> bool {
+        self.segments.len() <= path.len()
+            && self
+                .segments
+                .iter()
+                .zip(path)
+                .all(|(segment, key)| match segment {
+                    Segment::Key(name) => name == key.as_ref(),
+                    Segment::Any => true,
+                })
+    }
+
+    /// Apply the scope's settings, unless their rules are forbidden.
+    pub(super) fn apply(&self, config: &mut Config) {
+        for setting in &self.settings {
+            setting.apply(config);
+        }
+    }
+}
+
+impl Segment {
+    fn parse(segment: &str) -> Result<Self, &'static str> {
+        if segment == "*" {
+            return Ok(Segment::Any);
+        }
+
+        let mut key = String::with_capacity(segment.len());
+        let mut chars = segment.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '~' => match chars.next() {
+                    Some('0') => key.push('~'),
+                    Some('1') => key.push('/'),
+                    _ => return Err("`~` must be followed by `0` or `1`"),
+                },
+                c => key.push(c),
+            }
+        }
+
+        Ok(Segment::Key(key))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn matches() {
+        let scope = Scope::new("/registers/*/fields/*/desc", Vec::new()).unwrap();
+
+        assert!(scope.matches(&["registers", "0", "fields", "a", "desc"]));
+        assert!(scope.matches(&["registers", "uart", "fields", "0", "desc", "x"]));
+        assert!(!scope.matches(&["registers", "0", "fields", "a", "name"]));
+        assert!(!scope.matches(&["registers", "0", "fields", "a"]));
+        assert!(!scope.matches::<&str>(&[]));
+
+        let scope = Scope::new("", Vec::new()).unwrap();
+        assert!(scope.matches::<&str>(&[]));
+        assert!(scope.matches(&["a"]));
+
+        let scope = Scope::new("/a~1b/~0c/", Vec::new()).unwrap();
+        assert!(scope.matches(&["a/b", "~c", ""]));
+        assert!(!scope.matches(&["a", "b", "~c", ""]));
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(
+            Scope::new("a/b", Vec::new()),
+            Err("patterns must be empty or start with `/`")
+        );
+        assert_eq!(
+            Scope::new("/a~2", Vec::new()),
+            Err("`~` must be followed by `0` or `1`")
+        );
+    }
+}
@@ -0,0 +1,351 @@
+//! Comments which suppress lints, such as:
+//!
+//! ```hjson
+//! # hjson-lint: allow(trailing-comma, missing-comma)
+//! ```
+//!
+//! A suppression comment applies to:
+//!
+//! - The whole file, if it comes before anything else in the file.
+//! - Its own line, if it comes after something else on that line.
+//! - The next line with anything other than comments on it, otherwise.
+//!
+//! Any text after the closing parenthesis is ignored, so it can give a reason.
+//! Suppressions which don't suppress anything are linted, unless their rule is
+//! allowed.
+
+use super::{Config, Details, Level, Lint, LintKind, LintSpan, Registry};
+use crate::lexer::{Span, TokenKind};
+use crate::parser::ast::{Array, Map, Node, Value};
+
+/// Text which starts a suppression comment, after the comment marker.
+const PREFIX: &str = "hjson-lint:";
+
+/// A rule allowed by a suppression comment.
+#[derive(Clone, Debug)]
+pub(super) struct Suppression {
+    /// The comment the suppression is written in.
+    comment: Span,
+    /// Name of the rule, which may not exist.
+    rule: String,
+    scope: Scope,
+    /// Whether the suppression has suppressed any lints.
+    used: bool,
+    /// Whether the suppression was ignored for a forbidden lint.
+    forbidden: bool,
+}
+
+/// Where a suppression applies.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Scope {
+    File,
+    Line(usize),
+}
+
+impl Suppression {
+    /// Find the suppressions written in the comments of a file.
+    pub(super) fn find(input: &str, root: &Map) -> Vec<Suppression> {
+        let mut spans = Spans::default();
+        spans.map(root);
+        spans.code.sort_by_key(|span| span.start.byte_offset);
+
+        let mut suppressions = Vec::new();
+        for comment in spans.comments {
+            let start = comment.start.byte_offset;
+            let text = &input[start..start + comment.len];
+            let Some(rules) = rules(text) else {
+                continue;
+            };
+
+            let line = comment.start.line;
+            let end_line = line + text.matches('\n').count();
+            let code_before = spans
+                .code
+                .iter()
+                .take_while(|code| code.start.byte_offset < start);
+
+            let scope = match code_before.last() {
+                None => Scope::File,
+                Some(code) if code.start.line == line => Scope::Line(line),
+                Some(_) => {
+                    let next = spans.code.iter().find(|code| code.start.line > end_line);
+                    Scope::Line(next.map_or(end_line + 1, |code| code.start.line))
+                }
+            };
+
+            suppressions.extend(rules.map(|rule| Suppression {
+                comment: comment.clone(),
+                rule: rule.to_string(),
+                scope,
+                used: false,
+                forbidden: false,
+            }));
+        }
+
+        suppressions
+    }
+
+    /// Whether this suppression applies to `lint`, ignoring whether the lint
+    /// is forbidden.
+    fn suppresses(&self, lint: &Lint) -> bool {
+        let in_scope = match self.scope {
+            Scope::File => true,
+            Scope::Line(line) => lint.span.start.line == line,
+        };
+
+        in_scope && lint.kind.name() == self.rule
+    }
+
+    /// A lint about this suppression not having suppressed anything.
+    fn unused(&self, registry: &Registry) -> Lint {
+        let known = registry.get(&self.rule).is_some()
+            || LintKind::ALL.iter().any(|kind| kind.name() == self.rule);
+        let note = match (known, self.scope) {
+            _ if self.forbidden => format!("`{}` is forbidden, so can't be allowed", self.rule),
+            (false, _) => format!("there is no rule named `{}`", self.rule),
+            (true, Scope::File) => format!("no `{}` lints were found in this file", self.rule),
+            (true, Scope::Line(line)) => {
+                format!("no `{}` lints were found on line {line}", self.rule)
+            }
+        };
+
+        Lint {
+            kind: LintKind::UnusedSuppression,
+            level: Level::Deny,
+            span: LintSpan {
+                start: self.comment.start,
+                len: self.comment.len,
+            },
+            fix: None,
+            details: Details {
+                notes: Vec::from([note]),
+                ..Details::default()
+            },
+        }
+    }
+}
+
+/// Remove the lints which are suppressed, returning lints for any
+/// suppressions which didn't suppress anything. Rules are looked up in
+/// `registry` to explain why.
+///
+/// Suppressions of rules which `config` allows aren't reported, as files may
+/// be shared with other configurations which need them.
+pub(super) fn apply(
+    suppressions: &mut [Suppression],
+    lints: &mut Vec<Lint>,
+    registry: &Registry,
+    config: &Config,
+) -> Vec<Lint> {
+    lints.retain(|lint| {
+        let suppression = suppressions.iter_mut().find(|s| s.suppresses(lint));
+        match suppression {
+            Some(suppression) if lint.level == Level::Forbid => {
+                suppression.forbidden = true;
+                true
+            }
+            Some(suppression) => {
+                suppression.used = true;
+                false
+            }
+            None => true,
+        }
+    });
+
+    suppressions
+        .iter()
+        .filter(|suppression| {
+            let allowed = registry
+                .get(&suppression.rule)
+                .is_some_and(|rule| rule.level(config) == Level::Allow);
+            !suppression.used && !allowed
+        })
+        .map(|suppression| suppression.unused(registry))
+        .collect()
+}
+
+/// The rules allowed by a comment, if it's a suppression comment.
+fn rules(comment: &str) -> Option<impl Iterator<Item = &str>> {
+    let text = if let Some(text) = comment.strip_prefix("//") {
+        text
+    } else if let Some(text) = comment.strip_prefix("/*") {
+        text.strip_suffix("*/").unwrap_or(text)
+    } else {
+        comment.strip_prefix('#')?
+    };
+
+    let text = text.trim_start().strip_prefix(PREFIX)?.trim_start();
+    let (rules, _reason) = text.strip_prefix("allow(")?.split_once(')')?;
+
+    Some(
+        rules
+            .split(',')
+            .map(str::trim)
+            .filter(|rule| !rule.is_empty()),
+    )
+}
+
+/// Comments and code found in a file.
+#[derive(Debug, Default)]
+struct Spans {
+    comments: Vec<Span>,
+    /// Everything other than comments and whitespace.
+    code: Vec<Span>,
+}
+
+impl Spans {
+    fn node<T>(&mut self, node: &Node<T>) {
+        let comments = node.before.iter().chain(&node.after).filter(|span| {
+            matches!(
+                span.kind,
+                TokenKind::LineComment | TokenKind::HashComment | TokenKind::BlockComment
+            )
+        });
+        self.comments.extend(comments.cloned());
+    }
+
+    fn map(&mut self, map: &Map) {
+        self.node(&map.open_brace);
+        self.code.extend(map.open_brace.inner.clone());
+
+        for member in &map.members {
+            self.node(member);
+            self.code.push(member.inner.key.clone());
+            self.node(&member.inner.colon);
+            self.code.push(member.inner.colon.inner.clone());
+            self.value(&member.inner.value);
+            self.node(&member.inner.comma);
+            self.code.extend(member.inner.comma.inner.clone());
+        }
+
+        self.node(&map.close_brace);
+        self.code.extend(map.close_brace.inner.clone());
+    }
+
+    fn array(&mut self, array: &Array) {
+        self.node(&array.open_bracket);
+        self.code.push(array.open_bracket.inner.clone());
+
+        for member in &array.members {
+            self.node(member);
+            self.value(&member.inner.value);
+            self.node(&member.inner.comma);
+            self.code.extend(member.inner.comma.inner.clone());
+        }
+
+        self.node(&array.close_bracket);
+        self.code.push(array.close_bracket.inner.clone());
+    }
+
+    fn value(&mut self, value: &Value) {
+        match value {
+            Value::Map(map) => self.map(map),
+            Value::Array(array) => self.array(array),
+            Value::Value(span) => self.code.push(span.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::linter::{AllowDenyRequire, Config, Linter};
+    use indoc::indoc;
+
+    fn lints(input: &str) -> Vec<(&'static str, usize)> {
+        Linter::lint(Config::strict(), input)
+            .unwrap()
+            .iter()
+            .map(|lint| (lint.kind().name(), lint.span().start().line))
+            .collect()
+    }
+
+    #[test]
+    fn scopes() {
+        let input = indoc! {r#"
+            {
+              "a": 1,
+              # hjson-lint: allow(trailing-comma) because reasons
+
+              "b": 2, // hjson-lint: allow(unquoted-value)
+              "c": [
+                3, /* hjson-lint: allow(trailing-comma) */
+              ],
+            }
+        "#};
+
+        assert_eq!(
+            lints(input),
+            [
+                ("unused-suppression", 3),
+                ("unused-suppression", 5),
+                ("trailing-comma", 8),
+            ]
+        );
+    }
+
+    #[test]
+    fn file_wide() {
+        let input = indoc! {r#"
+            // hjson-lint: allow(unquoted-key, missing-comma)
+            # hjson-lint: allow(trailing-comma)
+            {
+              a: 1
+              b: 2
+            }
+        "#};
+
+        assert_eq!(lints(input), [("unused-suppression", 2)]);
+    }
+
+    #[test]
+    fn allowed_rules() {
+        let input = indoc! {r#"
+            {
+              "a": 1, # hjson-lint: allow(trailing-comma, unknown-rule)
+            }
+        "#};
+
+        // Other configurations may need the suppression for a rule which this
+        // one allows, but no configuration has an unknown rule.
+        let config = Config {
+            trailing_commas: AllowDenyRequire::Allow,
+            ..Config::strict()
+        };
+        let lints: Vec<_> = Linter::lint(&config, input)
+            .unwrap()
+            .iter()
+            .flat_map(|lint| lint.details().notes.clone())
+            .collect();
+        assert_eq!(lints, ["there is no rule named `unknown-rule`"]);
+    }
+
+    #[test]
+    fn unused() {
+        let input = indoc! {r#"
+            {
+              "a": "b" # hjson-lint: allow(trailing-comma, unknown-rule)
+            }
+        "#};
+
+        let lints = Linter::lint(Config::strict(), input).unwrap();
+        let notes: Vec<_> = lints
+            .iter()
+            .flat_map(|lint| lint.details().notes.clone())
+            .collect();
+
+        assert_eq!(
+            notes,
+            [
+                "no `trailing-comma` lints were found on line 2",
+                "there is no rule named `unknown-rule`",
+            ]
+        );
+
+        // Unused suppressions can themselves be allowed.
+        let config = Config {
+            unused_suppressions: crate::linter::AllowDeny::Allow,
+            ..Config::strict()
+        };
+        assert_eq!(Linter::lint(&config, input).unwrap(), Vec::new());
+    }
+}
@@ -1,11 +1,589 @@
-use std::io;
+use std::collections::HashMap;
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::{self, Path, PathBuf};
+use std::process::ExitCode;
 
-use hjson_lint::linter::{Config, Linter};
+use hjson_lint::baseline::Baseline;
+use hjson_lint::convert;
+use hjson_lint::files::{self, Input};
+use hjson_lint::fix;
+use hjson_lint::linter::config::PRESETS;
+use hjson_lint::linter::{Config, ConfigFile, Level, Lint, LintKind, Linter};
+use hjson_lint::render::{Format, Report};
 
-fn main() {
-    let input = io::read_to_string(io::stdin()).expect("failed to read stdin");
+const USAGE: &str = "\
+Usage: hjson-lint [OPTIONS] [PATH]...
+       hjson-lint convert [OPTIONS] --to <FORMAT> [FILE]
+       hjson-lint --explain <RULE>
 
-    let lints = Linter::lint(Config::strict(), &input).expect("failed to lint");
+Lint Hjson files. Each PATH may be a file, a directory to search recursively,
+a glob pattern such as 'configs/**/*.hjson', or `-` for standard input. If no
+paths are given, standard input is linted.
 
-    println!("{lints:#?}");
+Each file is linted using the nearest `.hjson-lint.hjson` configuration file in
+its directory or any parent directory, or using strict rules if there is none.
+Files which the configuration file ignores are skipped.
+
+Options:
+  -c, --config <FILE>    Configuration file to use for all files instead of
+                         searching for one
+  -p, --preset <NAME>    Preset rules to use for all files instead of
+                         searching for a configuration file: default,
+                         strict, jsonc, hjson-canonical, or relaxed-hjson
+  -e, --extension <EXT>  Extension of files to find in directories and globs
+                         (may be repeated, defaults to `hjson`)
+  -f, --format <FORMAT>  Format to print lints in: text, json, sarif, or
+                         github (defaults to text)
+      --fix              Fix lints where possible, rewriting files in place,
+                         and report any lints which remain (files with
+                         syntax errors are not fixed)
+      --diff             Print the changes `--fix` would make as a diff,
+                         without changing any files, and report every lint
+  -A, --allow <RULE>     Allow a rule, overriding configuration files
+  -W, --warn <RULE>      Warn about a rule, overriding configuration files
+  -D, --deny <RULE>      Deny a rule, overriding configuration files
+  -F, --forbid <RULE>    Deny a rule and ignore any later attempts to allow it,
+                         including suppression comments
+      --max-warnings <N> Fail if more than N warnings are found
+      --baseline <FILE>  Only report lints which aren't in a baseline file
+      --write-baseline   Record every lint found in the `--baseline` file,
+                         replacing its contents
+      --explain <RULE>   Explain a rule, given by its name or code, with
+                         examples
+  -h, --help             Print this help message
+
+Lints match a baseline by file, rule, and the contents of their line, so they
+still match after other lines are changed.
+
+Rule levels may be repeated, and apply in order, so `-A unquoted-key -W
+unquoted-key` warns about unquoted keys.
+
+Exits with 1 if any syntax errors or denied lints were found, if there are more
+warnings than `--max-warnings`, or (with `--diff`) if any fixes would be made.
+Exits with 2 if any file could not be linted.";
+
+const CONVERT_USAGE: &str = "\
+Usage: hjson-lint convert [OPTIONS] --to <FORMAT> [FILE]
+
+Convert a file between Hjson and JSON, printing the result. FILE may be `-` for
+standard input, which is converted if no file is given.
+
+Hjson is written to pass the nearest `.hjson-lint.hjson` configuration file, or
+strict rules if there is none. Conversion fails rather than change any numbers
+or strings.
+
+Options:
+  -t, --to <FORMAT>      Format to convert to: json or hjson
+      --pretty           Print JSON over multiple lines
+  -c, --config <FILE>    Configuration file for the Hjson to pass instead of
+                         searching for one
+  -p, --preset <NAME>    Preset rules for the Hjson to pass instead of
+                         searching for a configuration file
+  -h, --help             Print this help message
+
+Exits with 2 if the file could not be converted.";
+
+/// Command-line arguments.
+#[derive(Debug, Default)]
+struct Args {
+    paths: Vec<String>,
+    config: Option<PathBuf>,
+    preset: Option<Config>,
+    extensions: Vec<String>,
+    format: Format,
+    fix: bool,
+    diff: bool,
+    levels: Vec<(String, Level)>,
+    max_warnings: Option<usize>,
+    baseline: Option<PathBuf>,
+    write_baseline: bool,
+    explain: Option<LintKind>,
+    help: bool,
+}
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Args::default();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-h" | "--help" => parsed.help = true,
+                "-c" | "--config" => {
+                    let config = args.next().ok_or(format!("{arg} requires a value"))?;
+                    parsed.config = Some(PathBuf::from(config));
+                }
+                "-p" | "--preset" => {
+                    let preset = args.next().ok_or(format!("{arg} requires a value"))?;
+                    parsed.preset = Some(parse_preset(&preset)?);
+                }
+                "-e" | "--extension" => {
+                    let ext = args.next().ok_or(format!("{arg} requires a value"))?;
+                    parsed.extensions.push(ext);
+                }
+                "-f" | "--format" => {
+                    let format = args.next().ok_or(format!("{arg} requires a value"))?;
+                    parsed.format = format.parse()?;
+                }
+                "--fix" => parsed.fix = true,
+                "--diff" => parsed.diff = true,
+                "-A" | "--allow" | "-W" | "--warn" | "-D" | "--deny" | "-F" | "--forbid" => {
+                    let rule = args.next().ok_or(format!("{arg} requires a value"))?;
+                    if LintKind::from_name(&rule).is_none() {
+                        return Err(format!("unknown rule `{rule}`"));
+                    }
+
+                    let level = match arg.as_str() {
+                        "-A" | "--allow" => Level::Allow,
+                        "-W" | "--warn" => Level::Warn,
+                        "-D" | "--deny" => Level::Deny,
+                        _ => Level::Forbid,
+                    };
+                    parsed.levels.push((rule, level));
+                }
+                "--max-warnings" => {
+                    let max = args.next().ok_or(format!("{arg} requires a value"))?;
+                    let max = max
+                        .parse()
+                        .map_err(|_| format!("invalid number `{max}` for {arg}"))?;
+                    parsed.max_warnings = Some(max);
+                }
+                "--baseline" => {
+                    let baseline = args.next().ok_or(format!("{arg} requires a value"))?;
+                    parsed.baseline = Some(PathBuf::from(baseline));
+                }
+                "--write-baseline" => parsed.write_baseline = true,
+                "--explain" => {
+                    let rule = args.next().ok_or(format!("{arg} requires a value"))?;
+                    let kind = LintKind::from_name(&rule).or_else(|| LintKind::from_code(&rule));
+                    parsed.explain = Some(kind.ok_or(format!("unknown rule `{rule}`"))?);
+                }
+                "--" => parsed.paths.extend(&mut args),
+                "-" => parsed.paths.push(arg),
+                _ if arg.starts_with('-') => return Err(format!("unknown option `{arg}`")),
+                _ => parsed.paths.push(arg),
+            }
+        }
+
+        if parsed.config.is_some() && parsed.preset.is_some() {
+            return Err(String::from(
+                "--config and --preset cannot be used together",
+            ));
+        }
+
+        if parsed.write_baseline && parsed.baseline.is_none() {
+            return Err(String::from("--write-baseline requires --baseline"));
+        }
+
+        if parsed.paths.is_empty() {
+            parsed.paths.push(String::from("-"));
+        }
+
+        if parsed.fix && parsed.diff {
+            return Err(String::from("--fix and --diff cannot be used together"));
+        }
+
+        Ok(parsed)
+    }
+}
+
+/// Configuration with the given preset name.
+fn parse_preset(name: &str) -> Result<Config, String> {
+    Config::preset(name).ok_or_else(|| {
+        format!(
+            "unknown preset `{name}`, expected one of: {}",
+            PRESETS.join(", ")
+        )
+    })
+}
+
+/// Formats which files can be converted to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum Target {
+    #[default]
+    Json,
+    Hjson,
+}
+
+/// Command-line arguments for `hjson-lint convert`.
+#[derive(Debug, Default)]
+struct ConvertArgs {
+    path: Option<String>,
+    to: Target,
+    pretty: bool,
+    config: Option<PathBuf>,
+    preset: Option<Config>,
+    help: bool,
+}
+
+impl ConvertArgs {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = ConvertArgs::default();
+        let mut paths = Vec::new();
+        let mut to = None;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-h" | "--help" => parsed.help = true,
+                "-t" | "--to" => {
+                    let value = args.next().ok_or(format!("{arg} requires a value"))?;
+                    parsed.to = match value.as_str() {
+                        "json" => Target::Json,
+                        "hjson" => Target::Hjson,
+                        _ => return Err(format!("unknown format `{value}`")),
+                    };
+                    to = Some(parsed.to);
+                }
+                "--pretty" => parsed.pretty = true,
+                "-c" | "--config" => {
+                    let config = args.next().ok_or(format!("{arg} requires a value"))?;
+                    parsed.config = Some(PathBuf::from(config));
+                }
+                "-p" | "--preset" => {
+                    let preset = args.next().ok_or(format!("{arg} requires a value"))?;
+                    parsed.preset = Some(parse_preset(&preset)?);
+                }
+                "--" => paths.extend(&mut args),
+                "-" => paths.push(arg),
+                _ if arg.starts_with('-') => return Err(format!("unknown option `{arg}`")),
+                _ => paths.push(arg),
+            }
+        }
+
+        if parsed.help {
+            return Ok(parsed);
+        }
+
+        if parsed.config.is_some() && parsed.preset.is_some() {
+            return Err(String::from(
+                "--config and --preset cannot be used together",
+            ));
+        }
+
+        if paths.len() > 1 {
+            return Err(String::from("only one file can be converted at a time"));
+        }
+        parsed.path = paths.pop();
+
+        match to {
+            None => Err(String::from("--to is required")),
+            Some(Target::Hjson) if parsed.pretty => {
+                Err(String::from("--pretty can only be used with `--to json`"))
+            }
+            Some(_) => Ok(parsed),
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let mut args = env::args().skip(1).peekable();
+    if args.next_if(|arg| arg == "convert").is_some() {
+        return convert(args);
+    }
+
+    let args = match Args::parse(args) {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("error: {err}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    if args.help {
+        println!("{USAGE}");
+        return ExitCode::SUCCESS;
+    }
+
+    if let Some(explanation) = args.explain.as_ref().and_then(LintKind::explain) {
+        print!("{explanation}");
+        return ExitCode::SUCCESS;
+    }
+
+    let extensions: Vec<&str> = match args.extensions.is_empty() {
+        true => files::DEFAULT_EXTENSIONS.to_vec(),
+        false => args.extensions.iter().map(String::as_str).collect(),
+    };
+
+    let inputs = match files::find(&args.paths, &extensions) {
+        Ok(inputs) => inputs,
+        Err(err) => {
+            eprintln!("error: {err}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let mut configs = Configs::default();
+    if let Some(path) = &args.config {
+        if let Err(err) = configs.set_explicit(path) {
+            eprintln!("error: {err}");
+            return ExitCode::from(2);
+        }
+    }
+    if let Some(preset) = &args.preset {
+        configs.set_preset(preset.clone());
+    }
+
+    let baseline = match &args.baseline {
+        Some(path) if !args.write_baseline => match Baseline::load(path) {
+            Ok(baseline) => Some(baseline),
+            Err(err) => {
+                eprintln!("error: {}: {err}", path.display());
+                return ExitCode::from(2);
+            }
+        },
+        _ => None,
+    };
+
+    let mut linted = true;
+    let mut pending = false;
+    let mut reports = Vec::new();
+    for input in &inputs {
+        match configs.ignores(input) {
+            Ok(true) => continue,
+            Ok(false) => (),
+            Err(err) => {
+                eprintln!("error: {input}: {err}");
+                linted = false;
+                continue;
+            }
+        }
+
+        match lint(input, &args, &mut configs) {
+            Ok((report, unfixed)) => {
+                reports.push(report);
+                pending |= unfixed;
+            }
+            Err(err) => {
+                eprintln!("error: {input}: {err}");
+                linted = false;
+            }
+        }
+    }
+
+    let baseline = match (&args.baseline, baseline) {
+        (Some(path), None) => {
+            let baseline = Baseline::new(&reports);
+            if let Err(err) = fs::write(path, baseline.to_json()) {
+                eprintln!("error: {}: {err}", path.display());
+                return ExitCode::from(2);
+            }
+            eprintln!("wrote {} lints to {}", baseline.len(), path.display());
+            Some(baseline)
+        }
+        (_, baseline) => baseline,
+    };
+    if let Some(baseline) = baseline {
+        baseline.filter(&mut reports);
+    }
+
+    print!("{}", args.format.render(&reports));
+
+    let warnings = reports
+        .iter()
+        .flat_map(|report| &report.lints)
+        .filter(|lint| lint.level() == Level::Warn)
+        .count();
+    let too_many_warnings = args.max_warnings.is_some_and(|max| warnings > max);
+    if let Some(max) = args.max_warnings.filter(|_| too_many_warnings) {
+        eprintln!("error: found {warnings} warnings, more than the maximum of {max}");
+    }
+
+    let failed = pending || too_many_warnings || reports.iter().any(Report::fails);
+
+    match (linted, failed) {
+        (false, _) => ExitCode::from(2),
+        (true, true) => ExitCode::from(1),
+        (true, false) => ExitCode::SUCCESS,
+    }
+}
+
+/// Run `hjson-lint convert`.
+fn convert(args: impl Iterator<Item = String>) -> ExitCode {
+    let args = match ConvertArgs::parse(args) {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("error: {err}\n\n{CONVERT_USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    if args.help {
+        println!("{CONVERT_USAGE}");
+        return ExitCode::SUCCESS;
+    }
+
+    let input = match args.path.as_deref() {
+        None | Some("-") => Input::Stdin,
+        Some(path) => Input::File(PathBuf::from(path)),
+    };
+
+    match convert_input(&input, &args) {
+        Ok(output) => {
+            print!("{output}");
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("error: {input}: {err}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// Convert a single input, returning the converted document.
+fn convert_input(input: &Input, args: &ConvertArgs) -> Result<String, Box<dyn Error>> {
+    let source = input.read()?;
+
+    let output = match args.to {
+        Target::Json => convert::to_json(&source, args.pretty)?,
+        Target::Hjson => {
+            let mut configs = Configs::default();
+            if let Some(path) = &args.config {
+                configs.set_explicit(path)?;
+            }
+            if let Some(preset) = &args.preset {
+                configs.set_preset(preset.clone());
+            }
+            convert::to_hjson(&source, &configs.get(input)?)?
+        }
+    };
+
+    Ok(output)
+}
+
+/// Lint a single input, fixing it first if requested.
+///
+/// Also returns whether there are fixes which were only shown as a diff and
+/// not applied.
+fn lint(
+    input: &Input,
+    args: &Args,
+    configs: &mut Configs,
+) -> Result<(Report, bool), Box<dyn Error>> {
+    let mut config = configs.get(input)?;
+    for (rule, level) in &args.levels {
+        config.set_level(rule, *level);
+    }
+
+    let mut source = input.read()?;
+    let (mut lints, errors) = Linter::lint_resilient(&config, &source);
+    let mut unfixed = false;
+
+    // Fixes can't be trusted in files which didn't fully parse.
+    if (args.fix || args.diff) && errors.is_empty() {
+        let fixed = fix::apply(&source, lints.iter().filter_map(Lint::fix));
+
+        if fixed.applied > 0 {
+            // Check the fixes didn't break anything before using them.
+            let remaining = Linter::lint(&config, &fixed.output)
+                .map_err(|err| format!("fixes produced invalid Hjson: {err}"))?;
+
+            if args.diff {
+                // The input is left as it is, so its lints are still reported
+                // against the original source rather than the fixed output.
+                print!("{}", fix::diff(&input.to_string(), &source, &fixed.edits));
+                unfixed = true;
+            } else {
+                match input {
+                    Input::File(path) => fs::write(path, &fixed.output)?,
+                    Input::Stdin => Err("cannot fix standard input, use --diff instead")?,
+                }
+
+                source = fixed.output;
+                lints = remaining;
+            }
+        }
+    }
+
+    let report = Report {
+        file: input.to_string(),
+        source,
+        errors,
+        lints,
+    };
+
+    Ok((report, unfixed))
+}
+
+/// Configuration for each input, loaded from configuration files as needed.
+#[derive(Debug, Default)]
+struct Configs {
+    /// Configuration file or preset given on the command line, used for all
+    /// inputs, with the directory its paths are relative to.
+    explicit: Option<(PathBuf, ConfigFile)>,
+    /// Configuration files which have already been loaded, by their
+    /// directories.
+    loaded: HashMap<PathBuf, ConfigFile>,
+}
+
+impl Configs {
+    /// Use the configuration file at `path` for all inputs.
+    fn set_explicit(&mut self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let file = ConfigFile::load(path).map_err(|err| format!("{}: {err}", path.display()))?;
+        let dir = path::absolute(path)?
+            .parent()
+            .map_or_else(PathBuf::new, Path::to_path_buf);
+        self.explicit = Some((dir, file));
+
+        Ok(())
+    }
+
+    /// Use a preset for all inputs.
+    fn set_preset(&mut self, config: Config) {
+        self.explicit = Some((PathBuf::new(), ConfigFile::from(config)));
+    }
+
+    /// Configuration for an input.
+    fn get(&mut self, input: &Input) -> Result<Config, Box<dyn Error>> {
+        Ok(match self.file(input)? {
+            Some((file, path)) => file.config_for(&path),
+            None => Config::strict(),
+        })
+    }
+
+    /// Whether an input is ignored by its configuration file.
+    fn ignores(&mut self, input: &Input) -> Result<bool, Box<dyn Error>> {
+        Ok(self
+            .file(input)?
+            .is_some_and(|(file, path)| file.ignores(&path)))
+    }
+
+    /// The configuration file for an input, if there is one, with the path of
+    /// the input relative to its directory.
+    fn file(&mut self, input: &Input) -> Result<Option<(&ConfigFile, PathBuf)>, Box<dyn Error>> {
+        let path = match input {
+            Input::Stdin => None,
+            Input::File(path) => Some(path::absolute(path)?),
+        };
+
+        let (dir, file) = match &self.explicit {
+            Some((dir, file)) => (dir.clone(), file),
+            None => {
+                let dir = match path.as_deref().and_then(Path::parent) {
+                    Some(dir) => dir.to_path_buf(),
+                    None => path::absolute(".")?,
+                };
+
+                let Some(found) = Config::find(&dir) else {
+                    return Ok(None);
+                };
+                let dir = found.parent().unwrap_or(&dir).to_path_buf();
+
+                if !self.loaded.contains_key(&dir) {
+                    let file = ConfigFile::load(&found)
+                        .map_err(|err| format!("{}: {err}", found.display()))?;
+                    self.loaded.insert(dir.clone(), file);
+                }
+                (dir.clone(), &self.loaded[&dir])
+            }
+        };
+
+        // Standard input has no path, so no patterns match it.
+        let relative = match &path {
+            Some(path) => path.strip_prefix(&dir).unwrap_or(path).to_path_buf(),
+            None => PathBuf::new(),
+        };
+
+        Ok(Some((file, relative)))
+    }
 }
@@ -0,0 +1,245 @@
+//! Lowering of the error-resilient tree from [`hjson_parser`] into tokens.
+//!
+//! The tree keeps everything that failed to parse in `ErrorTree`s, and still
+//! builds mappings which are missing their colon or value. The tokens of each
+//! of these are left out, so that the rest of the file can be parsed into an
+//! AST and linted as normal. Braces and brackets left unclosed at the end of
+//! the file are closed with zero-length tokens.
+//!
+//! The syntax errors themselves come from the parser, and are converted into
+//! [`ParseError`]s.
+
+use hjson_parser::{Child, Token, Tree, TreeKind};
+
+use super::ParseError;
+use crate::lexer::{Cursor, Span, TokenKind};
+
+/// Tokens from a tree with anything that failed to parse removed.
+pub struct Lowered {
+    pub tokens: Vec<Span>,
+    pub errors: Vec<ParseError>,
+}
+
+/// Lower the tree parsed from `input`, along with its syntax errors.
+pub fn lower(input: &str, tree: &Tree, errors: &[hjson_parser::Error]) -> Lowered {
+    let mut lowering = Lowering {
+        input,
+        cursor: Cursor::default(),
+        tokens: Vec::new(),
+    };
+
+    lowering.file(tree);
+
+    let mut tokens = lowering.tokens;
+    tokens.push(Span::new(TokenKind::Eof, lowering.cursor, 0));
+
+    let errors = errors
+        .iter()
+        .map(|error| ParseError {
+            got: Span::new(
+                kind(error.got),
+                Cursor::at(input, error.range.start),
+                error.range.len(),
+            ),
+            expected: error.describe_expected(),
+        })
+        .collect();
+
+    Lowered { tokens, errors }
+}
+
+struct Lowering<'a> {
+    input: &'a str,
+    cursor: Cursor,
+    tokens: Vec<Span>,
+}
+
+impl Lowering<'_> {
+    fn file(&mut self, tree: &Tree) {
+        for child in tree.children() {
+            match child {
+                Child::Token(token) => self.token(token),
+                Child::Tree(map) if map.kind() == TreeKind::Map => self.map(map),
+                Child::Tree(tree) => self.skip_tree(tree),
+            }
+        }
+    }
+
+    fn map(&mut self, tree: &Tree) {
+        for child in tree.children() {
+            match child {
+                Child::Token(token) => self.token(token),
+                Child::Tree(mapping) if mapping.kind() == TreeKind::Mapping => {
+                    self.mapping(mapping)
+                }
+                Child::Tree(tree) => self.skip_tree(tree),
+            }
+        }
+
+        let (open, close) = (
+            hjson_parser::TokenKind::LBrace,
+            hjson_parser::TokenKind::RBrace,
+        );
+        if self.unclosed(tree, open, close) {
+            self.close(TokenKind::CloseBrace);
+        }
+    }
+
+    fn mapping(&mut self, tree: &Tree) {
+        // Mappings are always built, even if they're missing parts, in which
+        // case the whole mapping is left out.
+        let significant = tree.children().iter().filter(|child| !is_separator(child));
+
+        let mut parts = 0;
+        let mut valid = true;
+        for (i, child) in significant.enumerate() {
+            valid &= match (i, child) {
+                (0, Child::Token(token)) => is_key(token.kind),
+                (1, Child::Token(token)) => token.kind == hjson_parser::TokenKind::Colon,
+                (2, Child::Token(token)) => is_value(token.kind),
+                (2, Child::Tree(tree)) => matches!(tree.kind(), TreeKind::Map | TreeKind::Array),
+                _ => false,
+            };
+            parts += 1;
+        }
+
+        if !valid || parts != 3 {
+            self.skip_tree(tree);
+            return;
+        }
+
+        for child in tree.children() {
+            match child {
+                Child::Token(token) => self.token(token),
+                Child::Tree(tree) if tree.kind() == TreeKind::Map => self.map(tree),
+                Child::Tree(tree) => self.array(tree),
+            }
+        }
+    }
+
+    fn array(&mut self, tree: &Tree) {
+        for child in tree.children() {
+            match child {
+                Child::Token(token) => self.token(token),
+                Child::Tree(tree) if tree.kind() == TreeKind::Map => self.map(tree),
+                Child::Tree(tree) if tree.kind() == TreeKind::Array => self.array(tree),
+                Child::Tree(tree) => self.skip_tree(tree),
+            }
+        }
+
+        let (open, close) = (
+            hjson_parser::TokenKind::LBracket,
+            hjson_parser::TokenKind::RBracket,
+        );
+        if self.unclosed(tree, open, close) {
+            self.close(TokenKind::CloseBracket);
+        }
+    }
+
+    /// Whether a tree was opened with `open` but not closed with `close`.
+    fn unclosed(
+        &self,
+        tree: &Tree,
+        open: hjson_parser::TokenKind,
+        close: hjson_parser::TokenKind,
+    ) -> bool {
+        let is = |child: Option<&Child>, kind| matches!(child, Some(Child::Token(token)) if token.kind == kind);
+        let children = tree.children();
+
+        is(children.first(), open) && !is(children.last(), close)
+    }
+
+    /// Close an unclosed map or array with a zero-length token.
+    fn close(&mut self, kind: TokenKind) {
+        self.tokens.push(Span::new(kind, self.cursor, 0));
+    }
+
+    /// Take a token from the tree.
+    fn token(&mut self, token: &Token) {
+        let span = self.advance(token);
+        self.tokens.push(span);
+    }
+
+    /// Leave out every token in a tree which failed to parse.
+    fn skip_tree(&mut self, tree: &Tree) {
+        for child in tree.children() {
+            match child {
+                Child::Token(token) => {
+                    self.advance(token);
+                }
+                Child::Tree(tree) => self.skip_tree(tree),
+            }
+        }
+    }
+
+    /// Move the cursor past a token, returning its span.
+    fn advance(&mut self, token: &Token) -> Span {
+        let start = self.cursor;
+        let text = &self.input[start.byte_offset..start.byte_offset + token.len];
+
+        self.cursor.byte_offset += token.len;
+        self.cursor.line += text.matches('\n').count();
+        match text.rfind('\n') {
+            Some(x) => self.cursor.column = token.len - x,
+            None => self.cursor.column += token.len,
+        }
+
+        Span::new(kind(token.kind), start, token.len)
+    }
+}
+
+fn is_separator(child: &Child) -> bool {
+    use hjson_parser::TokenKind::*;
+
+    matches!(
+        child,
+        Child::Token(Token {
+            kind: Whitespace | NewLine | LineComment | HashComment | BlockComment,
+            ..
+        })
+    )
+}
+
+fn is_key(kind: hjson_parser::TokenKind) -> bool {
+    use hjson_parser::TokenKind::*;
+
+    matches!(kind, TextSingle | TextDouble | TextUnquoted)
+}
+
+fn is_value(kind: hjson_parser::TokenKind) -> bool {
+    use hjson_parser::TokenKind::*;
+
+    matches!(
+        kind,
+        Bool | Null | Integer | Float | TextSingle | TextDouble | TextMulti | TextUnquoted
+    )
+}
+
+/// The linter's kind for a token from the tree.
+fn kind(kind: hjson_parser::TokenKind) -> TokenKind {
+    use hjson_parser::TokenKind as Tree;
+
+    match kind {
+        Tree::Eof => TokenKind::Eof,
+        Tree::Bool => TokenKind::Boolean,
+        Tree::LineComment => TokenKind::LineComment,
+        Tree::BlockComment => TokenKind::BlockComment,
+        Tree::HashComment => TokenKind::HashComment,
+        Tree::Null => TokenKind::Null,
+        Tree::Integer => TokenKind::Integer,
+        Tree::Float => TokenKind::Float,
+        Tree::LBrace => TokenKind::OpenBrace,
+        Tree::RBrace => TokenKind::CloseBrace,
+        Tree::LBracket => TokenKind::OpenBracket,
+        Tree::RBracket => TokenKind::CloseBracket,
+        Tree::Colon => TokenKind::Colon,
+        Tree::Comma => TokenKind::Comma,
+        Tree::TextSingle => TokenKind::TextSingle,
+        Tree::TextDouble => TokenKind::TextDouble,
+        Tree::TextMulti => TokenKind::TextMulti,
+        Tree::TextUnquoted => TokenKind::TextUnquoted,
+        Tree::NewLine => TokenKind::NewLine,
+        Tree::Whitespace => TokenKind::Whitespace,
+        Tree::Error => TokenKind::Error,
+    }
+}
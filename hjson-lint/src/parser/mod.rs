@@ -3,20 +3,27 @@ use std::fmt::{self, Display};
 use std::iter;
 use std::iter::Peekable;
 use std::mem;
+use std::vec;
 
-use crate::lexer::{Cursor, Span, TokenKind, Tokens};
+use crate::lexer::{Cursor, Span, TokenKind};
 
 type ParseResult<T> = Result<T, ParseError>;
 
 pub mod ast;
+mod lower;
 
 use ast::Node;
 
-pub struct Parser<'a> {
-    tokens: Peekable<Tokens<'a>>,
+/// Parser building a format-preserving AST.
+///
+/// Syntax is checked by the error-resilient parser in [`hjson_parser`], and
+/// anything it could not parse is left out of the tokens given to this
+/// parser. The AST can then be built from the rest of the file.
+pub struct Parser {
+    tokens: Peekable<vec::IntoIter<Span>>,
 }
 
-impl<'a> Parser<'a> {
+impl Parser {
     const HIDDEN: &'static [TokenKind] = &[
         TokenKind::Whitespace,
         TokenKind::NewLine,
@@ -49,11 +56,39 @@ impl<'a> Parser<'a> {
         TokenKind::Null,
     ];
 
-    pub fn parse(input: &'a str) -> ParseResult<ast::Map> {
-        let tokens = Tokens::parse(input).peekable();
-        let mut parser = Self { tokens };
+    /// Parse a whole file, failing at the first syntax error.
+    pub fn parse(input: &str) -> ParseResult<ast::Map> {
+        let (root, mut errors) = Self::parse_resilient(input);
 
-        parser.parse_root()
+        match errors.is_empty() {
+            true => Ok(root),
+            false => Err(errors.remove(0)),
+        }
+    }
+
+    /// Parse a whole file, returning every syntax error along with an AST of
+    /// the parts of the file which could be parsed.
+    pub fn parse_resilient(input: &str) -> (ast::Map, Vec<ParseError>) {
+        let (tree, errors) = hjson_parser::parse(input);
+        let lowered = lower::lower(input, &tree, &errors);
+        let mut errors = lowered.errors;
+
+        let mut parser = Self {
+            tokens: lowered.tokens.into_iter().peekable(),
+        };
+        let root = parser.parse_root().unwrap_or_else(|err| {
+            // Everything should have been checked already, but don't lose the
+            // error if not.
+            errors.push(err);
+            ast::Map {
+                open_brace: Node::new(Vec::new(), None, Vec::new()),
+                members: Vec::new(),
+                close_brace: Node::new(Vec::new(), None, Vec::new()),
+            }
+        });
+
+        errors.sort_by_key(|err| err.got.start.byte_offset);
+        (root, errors)
     }
 
     fn parse_root(&mut self) -> ParseResult<ast::Map> {
@@ -236,9 +271,7 @@ impl<'a> Parser<'a> {
 
     #[must_use]
     fn eat(&mut self, kinds: &[TokenKind]) -> Option<Span> {
-        let Some(next) = self.tokens.peek() else {
-            return None;
-        };
+        let next = self.tokens.peek()?;
 
         if kinds.contains(&next.kind) {
             // If EOF, give the peeked token without taking it off the iterator.
@@ -271,12 +304,31 @@ impl<'a> Parser<'a> {
     }
 }
 
-#[derive(Clone, Debug)]
+/// A syntax error: something other than what was expected.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ParseError {
     got: Span,
     expected: String,
 }
 
+impl ParseError {
+    /// The token found instead of what was expected. Zero-length tokens are
+    /// given at the end of the file.
+    pub fn got(&self) -> &Span {
+        &self.got
+    }
+
+    /// A description of what was expected.
+    pub fn expected(&self) -> &str {
+        &self.expected
+    }
+
+    /// The error without its position.
+    pub fn message(&self) -> String {
+        format!("expected {}, got {}", self.expected, self.got.kind)
+    }
+}
+
 impl Display for ParseError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let Self {
@@ -293,3 +345,78 @@ impl Display for ParseError {
 }
 
 impl Error for ParseError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use indoc::indoc;
+
+    fn errors(input: &str) -> Vec<String> {
+        let (_, errors) = Parser::parse_resilient(input);
+        errors.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn valid() {
+        assert_eq!(errors(""), Vec::<String>::new());
+        assert_eq!(errors("// comment\n"), Vec::<String>::new());
+        assert_eq!(errors("a: 1,\nb: [2, 3]\n"), Vec::<String>::new());
+        assert_eq!(errors("{ 10: true, true: 10 }"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn all_errors() {
+        let input = indoc! {"
+            {
+              a: 1
+              b 2
+              c: [1,, 2]
+              d: 'e
+              f: ,
+            }
+            g
+        "};
+
+        assert_eq!(
+            errors(input),
+            [
+                "3:5: expected :, got unquoted string",
+                "4:9: expected value, got ,",
+                "5:6: expected value, got invalid token",
+                "6:6: expected value, got ,",
+                "8:1: expected end of file, got unquoted string",
+            ]
+        );
+    }
+
+    #[test]
+    fn unclosed() {
+        assert_eq!(
+            errors("{\n  a: [1, 2\n"),
+            ["3:1: expected ], got EOF", "3:1: expected }, got EOF"]
+        );
+    }
+
+    #[test]
+    fn partial_ast() {
+        let (root, _) = Parser::parse_resilient("a: 1\nb c\nd: [1\n");
+        let keys: Vec<usize> = root
+            .members
+            .iter()
+            .map(|member| member.inner.key.start.byte_offset)
+            .collect();
+
+        // `b c` is left out, while the array is closed at the end of the file.
+        assert_eq!(keys, [0, 9]);
+        assert!(matches!(
+            root.members[1].inner.value,
+            ast::Value::Array(ast::Array {
+                close_bracket: Node {
+                    inner: Span { len: 0, .. },
+                    ..
+                },
+                ..
+            })
+        ));
+    }
+}
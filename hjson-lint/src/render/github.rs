@@ -0,0 +1,126 @@
+//! Rendering as [GitHub workflow commands], which GitHub Actions turns into
+//! annotations on pull requests:
+//!
+//! ```text
+//! ::error file=config.hjson,line=2,col=9,endLine=2,endColumn=10,title=trailing-comma::trailing comma
+//! ```
+//!
+//! Warnings are written as `::warning` commands instead. Labelled spans, notes,
+//! and help are added as extra lines of the message.
+//!
+//! [GitHub workflow commands]: https://docs.github.com/en/actions/using-workflows/workflow-commands-for-github-actions
+
+use super::Report;
+
+pub fn render(reports: &[Report]) -> String {
+    let mut out = String::new();
+
+    for report in reports {
+        for entry in report.entries() {
+            let start = entry.start;
+            let end = super::end(&report.source, entry.start, entry.len);
+
+            // Annotations only have one location, so labels are listed in the
+            // message.
+            let mut message = entry.message.clone();
+            for label in &entry.details.labels {
+                let at = label.span().start();
+                message += &format!("\n{}:{}: {}", at.line, at.column, label.message());
+            }
+            message += &entry.notes_text();
+
+            let command = match entry.severity {
+                "warn" => "warning",
+                _ => "error",
+            };
+
+            out += &format!(
+                "::{command} file={},line={},col={},endLine={},endColumn={},title={}::{}\n",
+                escape_property(&report.file),
+                start.line,
+                start.column,
+                end.line,
+                end.column,
+                escape_property(entry.rule),
+                escape_data(&message),
+            );
+        }
+    }
+
+    out
+}
+
+/// Escape the message of a workflow command.
+fn escape_data(s: &str) -> String {
+    s.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escape the value of a workflow command property.
+fn escape_property(s: &str) -> String {
+    escape_data(s).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::linter::{Config, Level, Linter};
+
+    #[test]
+    fn lints() {
+        let source = "{\n'foo': 3,\n}";
+        let report = Report {
+            file: String::from("dir,with:odd%chars/test.hjson"),
+            source: source.to_string(),
+            errors: Vec::new(),
+            lints: Linter::lint(Config::strict(), source).unwrap(),
+        };
+
+        assert_eq!(
+            render(&[report]),
+            "::error file=dir%2Cwith%3Aodd%25chars/test.hjson,line=2,col=9,endLine=2,endColumn=10,\
+                title=trailing-comma::trailing comma\n"
+        );
+    }
+
+    #[test]
+    fn details() {
+        let source = "{\n'a': 1,\n'a': 2\n}";
+        let report = Report {
+            file: String::from("test.hjson"),
+            source: source.to_string(),
+            errors: Vec::new(),
+            lints: Linter::lint(Config::strict(), source).unwrap(),
+        };
+
+        assert_eq!(
+            render(&[report]),
+            "::error file=test.hjson,line=3,col=1,endLine=3,endColumn=4,title=duplicate-key::\
+                duplicate key%0A2:1: first defined here%0A\
+                note: parsers disagree on which value to use for a repeated key%0A\
+                help: remove or rename one of the keys\n"
+        );
+    }
+
+    #[test]
+    fn warnings() {
+        let mut config = Config::strict();
+        config.set_level("trailing-comma", Level::Warn);
+
+        let source = "{\n'foo': 3,\n}";
+        let report = Report {
+            file: String::from("test.hjson"),
+            source: source.to_string(),
+            errors: Vec::new(),
+            lints: Linter::lint(&config, source).unwrap(),
+        };
+
+        assert!(!report.fails());
+        assert_eq!(
+            render(&[report]),
+            "::warning file=test.hjson,line=2,col=9,endLine=2,endColumn=10,\
+                title=trailing-comma::trailing comma\n"
+        );
+    }
+}
@@ -0,0 +1,141 @@
+//! JSON rendering, as an array with one object per syntax error or lint:
+//!
+//! ```json
+//! [
+//!   {
+//!     "file": "config.hjson",
+//!     "rule": "trailing-comma",
+//!     "code": "HL003",
+//!     "severity": "deny",
+//!     "message": "trailing comma",
+//!     "start": { "line": 2, "column": 9, "byte": 8 },
+//!     "end": { "line": 2, "column": 10, "byte": 9 }
+//!   }
+//! ]
+//! ```
+//!
+//! Syntax errors have the rule `syntax-error` and the severity `error`, and
+//! only lints from built-in rules have a `code`. Lints may also have:
+//!
+//! - `labels`: other spans involved, as objects with a `message`, `start`, and
+//!   `end`, such as the first of some duplicate keys.
+//! - `notes`: an array of strings explaining the lint.
+//! - `help`: a string suggesting how to resolve it.
+
+use serde_json::{json, Value};
+
+use super::Report;
+use crate::lexer::Cursor;
+
+pub fn render(reports: &[Report]) -> String {
+    let entries: Vec<Value> = reports
+        .iter()
+        .flat_map(|report| {
+            report.entries().map(|entry| {
+                let start = entry.start;
+                let end = super::end(&report.source, start, entry.len);
+
+                let mut object = json!({
+                    "file": report.file,
+                    "rule": entry.rule,
+                    "severity": entry.severity,
+                    "message": entry.message,
+                    "start": position(start),
+                    "end": position(end),
+                });
+                if let Some(code) = entry.code {
+                    object["code"] = json!(code);
+                }
+
+                let details = &entry.details;
+                if !details.labels.is_empty() {
+                    let labels = details.labels.iter().map(|label| {
+                        let span = label.span();
+                        let end = super::end(&report.source, span.start(), span.len());
+                        json!({
+                            "message": label.message(),
+                            "start": position(span.start()),
+                            "end": position(end),
+                        })
+                    });
+                    object["labels"] = labels.collect();
+                }
+                if !details.notes.is_empty() {
+                    object["notes"] = json!(details.notes);
+                }
+                if let Some(help) = &details.help {
+                    object["help"] = json!(help);
+                }
+
+                object
+            })
+        })
+        .collect();
+
+    serde_json::to_string_pretty(&entries).expect("failed to serialize JSON") + "\n"
+}
+
+fn position(cursor: Cursor) -> Value {
+    json!({
+        "line": cursor.line,
+        "column": cursor.column,
+        "byte": cursor.byte_offset,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::linter::{Config, Linter};
+
+    #[test]
+    fn lints() {
+        let source = "{\n'foo': 3,\n}";
+        let report = Report {
+            file: String::from("test.hjson"),
+            source: source.to_string(),
+            errors: Vec::new(),
+            lints: Linter::lint(Config::strict(), source).unwrap(),
+        };
+
+        let rendered: Value = serde_json::from_str(&render(&[report])).unwrap();
+        assert_eq!(
+            rendered,
+            json!([{
+                "file": "test.hjson",
+                "rule": "trailing-comma",
+                "code": "HL003",
+                "severity": "deny",
+                "message": "trailing comma",
+                "start": { "line": 2, "column": 9, "byte": 10 },
+                "end": { "line": 2, "column": 10, "byte": 11 },
+            }])
+        );
+    }
+
+    #[test]
+    fn details() {
+        let source = "{\n\"a\": 1,\n\"a\": 2\n}";
+        let report = Report {
+            file: String::from("test.hjson"),
+            source: source.to_string(),
+            errors: Vec::new(),
+            lints: Linter::lint(Config::strict(), source).unwrap(),
+        };
+
+        let rendered: Value = serde_json::from_str(&render(&[report])).unwrap();
+        assert_eq!(
+            rendered[0]["labels"],
+            json!([{
+                "message": "first defined here",
+                "start": { "line": 2, "column": 1, "byte": 2 },
+                "end": { "line": 2, "column": 4, "byte": 5 },
+            }])
+        );
+        assert_eq!(
+            rendered[0]["notes"],
+            json!(["parsers disagree on which value to use for a repeated key"])
+        );
+        assert_eq!(rendered[0]["help"], "remove or rename one of the keys");
+    }
+}
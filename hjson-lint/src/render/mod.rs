@@ -0,0 +1,195 @@
+//! Rendering of [`Lint`]s for display to users and other tools.
+//!
+//! Lints can be rendered in several [`Format`]s: human-readable [`Text`],
+//! JSON, [SARIF] for code-scanning tools, and [GitHub workflow commands] so
+//! that lints appear as annotations on pull requests.
+//!
+//! [SARIF]: https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html
+//! [GitHub workflow commands]: https://docs.github.com/en/actions/using-workflows/workflow-commands-for-github-actions
+
+mod github;
+mod json;
+mod sarif;
+mod text;
+
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use crate::lexer::Cursor;
+use crate::linter::{Details, Level, Lint};
+use crate::parser::ParseError;
+
+pub use text::Text;
+
+/// Syntax errors and lints found in a single file, along with the source they
+/// were found in.
+#[derive(Clone, Debug)]
+pub struct Report {
+    /// Name of the file as it should be displayed.
+    pub file: String,
+    /// Contents of the file.
+    pub source: String,
+    /// Syntax errors found in the file.
+    pub errors: Vec<ParseError>,
+    /// Lints found in the file.
+    pub lints: Vec<Lint>,
+}
+
+impl Report {
+    /// Whether nothing was found in the file.
+    pub fn is_clean(&self) -> bool {
+        self.errors.is_empty() && self.lints.is_empty()
+    }
+
+    /// Whether anything found in the file should fail linting, which is
+    /// anything other than warnings.
+    pub fn fails(&self) -> bool {
+        !self.errors.is_empty() || self.lints.iter().any(|lint| lint.level() > Level::Warn)
+    }
+
+    /// Syntax errors followed by lints, as they are rendered.
+    fn entries(&self) -> impl Iterator<Item = Entry> + '_ {
+        let errors = self.errors.iter().map(Entry::from);
+        let lints = self.lints.iter().map(Entry::from);
+        errors.chain(lints)
+    }
+}
+
+/// Rule name reported for syntax errors.
+const SYNTAX_ERROR: &str = "syntax-error";
+
+/// A syntax error or lint in the form it's rendered in.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Entry {
+    rule: &'static str,
+    /// Stable code of the rule, if it's built in.
+    code: Option<&'static str>,
+    severity: &'static str,
+    message: String,
+    start: Cursor,
+    len: usize,
+    details: Details,
+}
+
+impl From<&ParseError> for Entry {
+    fn from(error: &ParseError) -> Self {
+        Entry {
+            rule: SYNTAX_ERROR,
+            code: None,
+            severity: "error",
+            message: error.message(),
+            start: error.got().start,
+            len: error.got().len,
+            details: Details::default(),
+        }
+    }
+}
+
+impl From<&Lint> for Entry {
+    fn from(lint: &Lint) -> Self {
+        Entry {
+            rule: lint.kind().name(),
+            code: lint.kind().code(),
+            severity: lint.level().name(),
+            message: lint.kind().to_string(),
+            start: lint.span().start(),
+            len: lint.span().len(),
+            details: lint.details().clone(),
+        }
+    }
+}
+
+/// Formats that lints can be rendered in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Format {
+    /// Human-readable text with source snippets.
+    #[default]
+    Text,
+    /// A JSON array with one object per lint.
+    Json,
+    /// A SARIF log for code-scanning tools.
+    Sarif,
+    /// GitHub Actions workflow commands for pull request annotations.
+    Github,
+}
+
+impl Format {
+    /// Render all lints in the given reports.
+    pub fn render(self, reports: &[Report]) -> String {
+        match self {
+            Format::Text => reports
+                .iter()
+                .flat_map(|report| {
+                    report.entries().map(|entry| {
+                        let text = Text::from_entry(&report.file, &report.source, entry);
+                        format!("{text}\n\n")
+                    })
+                })
+                .collect(),
+            Format::Json => json::render(reports),
+            Format::Sarif => sarif::render(reports),
+            Format::Github => github::render(reports),
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "sarif" => Ok(Format::Sarif),
+            "github" => Ok(Format::Github),
+            _ => Err(format!("unknown format `{s}`")),
+        }
+    }
+}
+
+impl Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Format::Text => "text",
+            Format::Json => "json",
+            Format::Sarif => "sarif",
+            Format::Github => "github",
+        };
+        f.write_str(name)
+    }
+}
+
+impl Entry {
+    /// Lines of notes and help to follow the message in formats without
+    /// their own place for them.
+    fn notes_text(&self) -> String {
+        let notes = self
+            .details
+            .notes
+            .iter()
+            .map(|note| format!("\nnote: {note}"));
+        let help = self
+            .details
+            .help
+            .iter()
+            .map(|help| format!("\nhelp: {help}"));
+        notes.chain(help).collect()
+    }
+}
+
+/// Find the position of the end of a span in the given source.
+fn end(source: &str, start: Cursor, len: usize) -> Cursor {
+    let from = start.byte_offset.min(source.len());
+    let to = (from + len).min(source.len());
+    let text = &source[from..to];
+
+    let mut end = start;
+    end.byte_offset += text.len();
+    end.line += text.matches('\n').count();
+    match text.rfind('\n') {
+        Some(x) => end.column = text.len() - x,
+        None => end.column += text.len(),
+    }
+
+    end
+}
@@ -0,0 +1,259 @@
+//! Rendering as a [SARIF] log, the format used by code-scanning tools.
+//!
+//! All reports are combined into a single run, and every built-in rule is
+//! listed in the run's tool description, followed by a `syntax-error` rule for
+//! syntax errors and then any other rules which were broken. Labelled spans
+//! become related locations, and notes and help are added to the message text.
+//!
+//! [SARIF]: https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html
+
+use serde_json::{json, Value};
+
+use super::{Report, SYNTAX_ERROR};
+use crate::lexer::Cursor;
+use crate::linter::LintKind;
+
+pub fn render(reports: &[Report]) -> String {
+    let mut rules: Vec<Value> = LintKind::ALL
+        .iter()
+        .map(|kind| {
+            json!({
+                "id": kind.name(),
+                "shortDescription": { "text": kind.to_string() },
+            })
+        })
+        .collect();
+    rules.push(json!({
+        "id": SYNTAX_ERROR,
+        "shortDescription": { "text": "syntax error" },
+    }));
+
+    // Rules which aren't built in are only known from their lints.
+    for entry in reports.iter().flat_map(Report::entries) {
+        if rules.iter().all(|rule| rule["id"] != entry.rule) {
+            rules.push(json!({
+                "id": entry.rule,
+                "shortDescription": { "text": entry.message },
+            }));
+        }
+    }
+
+    let results: Vec<Value> = reports
+        .iter()
+        .flat_map(|report| {
+            report.entries().map(|entry| {
+                let start = entry.start;
+                let index = rules.iter().position(|rule| rule["id"] == entry.rule);
+
+                let mut result = json!({
+                    "ruleId": entry.rule,
+                    "ruleIndex": index,
+                    "level": match entry.severity {
+                        "warn" => "warning",
+                        _ => "error",
+                    },
+                    "message": { "text": entry.message.clone() + &entry.notes_text() },
+                    "locations": [{
+                        "physicalLocation": location(report, start, entry.len),
+                    }],
+                });
+
+                let labels = &entry.details.labels;
+                if !labels.is_empty() {
+                    let related = labels.iter().enumerate().map(|(id, label)| {
+                        let span = label.span();
+                        json!({
+                            "id": id,
+                            "physicalLocation": location(report, span.start(), span.len()),
+                            "message": { "text": label.message() },
+                        })
+                    });
+                    result["relatedLocations"] = related.collect();
+                }
+
+                result
+            })
+        })
+        .collect();
+
+    let log = json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": "https://github.com/jwnrt/hjson-lint",
+                    "rules": rules,
+                },
+            },
+            "results": results,
+        }],
+    });
+
+    serde_json::to_string_pretty(&log).expect("failed to serialize SARIF") + "\n"
+}
+
+/// Physical location of a span in a report's file.
+fn location(report: &Report, start: Cursor, len: usize) -> Value {
+    let end = super::end(&report.source, start, len);
+
+    json!({
+        "artifactLocation": { "uri": report.file },
+        "region": {
+            "startLine": start.line,
+            "startColumn": column(&report.source, start),
+            "endLine": end.line,
+            "endColumn": column(&report.source, end),
+            "byteOffset": start.byte_offset,
+            "byteLength": len,
+        },
+    })
+}
+
+/// Column of a position in UTF-16 code units, which SARIF counts in by default.
+fn column(source: &str, at: Cursor) -> usize {
+    let to = at.byte_offset.min(source.len());
+    let from = to.saturating_sub(at.column - 1);
+
+    match source.get(from..to) {
+        Some(line) => line.encode_utf16().count() + 1,
+        None => at.column,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::linter::{Config, Lint, Linter};
+
+    #[test]
+    fn lints() {
+        let source = "'foo': 3";
+        let report = Report {
+            file: String::from("test.hjson"),
+            source: source.to_string(),
+            errors: Vec::new(),
+            lints: Linter::lint(Config::strict(), source).unwrap(),
+        };
+
+        let log: Value = serde_json::from_str(&render(&[report])).unwrap();
+        let run = &log["runs"][0];
+
+        assert_eq!(log["version"], "2.1.0");
+        assert_eq!(
+            run["tool"]["driver"]["rules"].as_array().unwrap().len(),
+            LintKind::ALL.len() + 1
+        );
+        assert_eq!(
+            run["results"],
+            json!([{
+                "ruleId": "implicit-braces",
+                "ruleIndex": 0,
+                "level": "error",
+                "message": { "text": "implicit braces" },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": "test.hjson" },
+                        "region": {
+                            "startLine": 1,
+                            "startColumn": 1,
+                            "endLine": 1,
+                            "endColumn": 1,
+                            "byteOffset": 0,
+                            "byteLength": 0,
+                        },
+                    },
+                }],
+            }])
+        );
+    }
+
+    #[test]
+    fn custom_rules() {
+        let kind = LintKind::Custom {
+            rule: "no-todo",
+            message: "unfinished work",
+        };
+        let report = Report {
+            file: String::from("test.hjson"),
+            source: String::from("# TODO"),
+            errors: Vec::new(),
+            lints: Vec::from([Lint::new(kind, Cursor::new(1, 1, 0), 6)]),
+        };
+
+        let log: Value = serde_json::from_str(&render(&[report])).unwrap();
+        let run = &log["runs"][0];
+        let index = LintKind::ALL.len() + 1;
+
+        assert_eq!(run["results"][0]["ruleIndex"], index);
+        assert_eq!(
+            run["tool"]["driver"]["rules"][index],
+            json!({
+                "id": "no-todo",
+                "shortDescription": { "text": "unfinished work" },
+            })
+        );
+    }
+
+    #[test]
+    fn columns() {
+        let kind = LintKind::Custom {
+            rule: "no-todo",
+            message: "unfinished work",
+        };
+        let report = Report {
+            file: String::from("test.hjson"),
+            source: String::from("# \u{e9}\u{1f600} TODO"),
+            errors: Vec::new(),
+            lints: Vec::from([Lint::new(kind, Cursor::new(1, 10, 9), 4)]),
+        };
+
+        let log: Value = serde_json::from_str(&render(&[report])).unwrap();
+        let region = &log["runs"][0]["results"][0]["locations"][0]["physicalLocation"]["region"];
+
+        assert_eq!(region["startColumn"], 7);
+        assert_eq!(region["endColumn"], 11);
+        assert_eq!(region["byteOffset"], 9);
+    }
+
+    #[test]
+    fn details() {
+        let source = "{\n'a': 1,\n'a': 2\n}";
+        let report = Report {
+            file: String::from("test.hjson"),
+            source: source.to_string(),
+            errors: Vec::new(),
+            lints: Linter::lint(Config::strict(), source).unwrap(),
+        };
+
+        let log: Value = serde_json::from_str(&render(&[report])).unwrap();
+        let result = &log["runs"][0]["results"][0];
+
+        assert_eq!(
+            result["message"]["text"],
+            "duplicate key\n\
+                note: parsers disagree on which value to use for a repeated key\n\
+                help: remove or rename one of the keys"
+        );
+        assert_eq!(
+            result["relatedLocations"],
+            json!([{
+                "id": 0,
+                "physicalLocation": {
+                    "artifactLocation": { "uri": "test.hjson" },
+                    "region": {
+                        "startLine": 2,
+                        "startColumn": 1,
+                        "endLine": 2,
+                        "endColumn": 4,
+                        "byteOffset": 2,
+                        "byteLength": 3,
+                    },
+                },
+                "message": { "text": "first defined here" },
+            }])
+        );
+    }
+}
@@ -0,0 +1,283 @@
+//! Human-readable rendering similar to `rustc`:
+//!
+//! ```text
+//! config.hjson:2:9: deny(trailing-comma): trailing comma
+//!   |
+//! 2 | 'foo': 3,
+//!   |         ^
+//! ```
+//!
+//! Syntax errors are rendered the same way, as `error(syntax-error)`. Other
+//! spans involved in a lint are underlined with `-` and labelled, and any notes
+//! or help follow:
+//!
+//! ```text
+//! config.hjson:3:1: deny(duplicate-key): duplicate key
+//!   |
+//! 1 | name: a
+//!   | ---- first defined here
+//! ...
+//! 3 | name: b
+//!   | ^^^^
+//!   |
+//!   = note: parsers disagree on which value to use for a repeated key
+//!   = help: remove or rename one of the keys
+//! ```
+
+use std::fmt::{self, Display};
+
+use super::Entry;
+use crate::lexer::Cursor;
+use crate::linter::Lint;
+use crate::parser::ParseError;
+
+/// Human-readable rendering of a lint or syntax error, including the offending
+/// source line.
+#[derive(Clone, Debug)]
+pub struct Text<'a> {
+    file: &'a str,
+    source: &'a str,
+    entry: Entry,
+}
+
+impl<'a> Text<'a> {
+    /// Render the `lint` found in `source`, which was read from `file`.
+    pub fn new(file: &'a str, source: &'a str, lint: &Lint) -> Self {
+        Self::from_entry(file, source, lint.into())
+    }
+
+    /// Render a syntax `error` found in `source`, which was read from `file`.
+    pub fn error(file: &'a str, source: &'a str, error: &ParseError) -> Self {
+        Self::from_entry(file, source, error.into())
+    }
+
+    pub(super) fn from_entry(file: &'a str, source: &'a str, entry: Entry) -> Self {
+        Self {
+            file,
+            source,
+            entry,
+        }
+    }
+}
+
+impl Display for Text<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self {
+            file,
+            source,
+            entry,
+        } = self;
+        let start = entry.start;
+
+        write!(
+            f,
+            "{file}:{}:{}: {}({}): {}",
+            start.line, start.column, entry.severity, entry.rule, entry.message
+        )?;
+
+        // The primary span and any labelled spans, in the order they appear.
+        let mut marks = Vec::from([Mark {
+            start,
+            len: entry.len,
+            primary: true,
+            label: None,
+        }]);
+        marks.extend(entry.details.labels.iter().map(|label| Mark {
+            start: label.span().start(),
+            len: label.span().len(),
+            primary: false,
+            label: Some(label.message()),
+        }));
+        marks.sort_by_key(|mark| mark.start.byte_offset);
+
+        let max_line = marks.iter().map(|mark| mark.start.line).max().unwrap_or(1);
+        let width = max_line.to_string().len();
+        let gutter = " ".repeat(width);
+
+        // Each line is shown once, followed by a row for each mark on it. Gaps
+        // between lines are elided.
+        write!(f, "\n{gutter} |")?;
+        let mut last_line = None;
+        for mark in &marks {
+            let line = mark.start.line;
+            if last_line != Some(line) {
+                if last_line.is_some_and(|last| line > last + 1) {
+                    write!(f, "\n...")?;
+                }
+                write!(f, "\n{line:>width$} | {}", source_line(source, mark.start))?;
+                last_line = Some(line);
+            }
+            write!(f, "\n{gutter} | {}", mark.underline(source))?;
+        }
+
+        let details = &entry.details;
+        if !details.notes.is_empty() || details.help.is_some() {
+            write!(f, "\n{gutter} |")?;
+        }
+        for note in &details.notes {
+            write!(f, "\n{gutter} = note: {note}")?;
+        }
+        if let Some(help) = &details.help {
+            write!(f, "\n{gutter} = help: {help}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A span to underline in the source.
+struct Mark<'a> {
+    start: Cursor,
+    len: usize,
+    /// Whether this is the lint's own span, rather than a labelled one.
+    primary: bool,
+    label: Option<&'a str>,
+}
+
+impl Mark<'_> {
+    /// Underline for the span, to go beneath its source line.
+    fn underline(&self, source: &str) -> String {
+        let offset = self.start.byte_offset.min(source.len());
+        let line_start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = source[offset..]
+            .find('\n')
+            .map_or(source.len(), |i| offset + i);
+
+        // Pad the underline to the start of the span, keeping tabs as tabs so
+        // that it lines up with the source line however tabs are displayed.
+        let padding: String = source[line_start..offset]
+            .chars()
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+
+        // Underline up to the end of the line, with at least one mark for
+        // zero-length spans.
+        let span_end = (offset + self.len).min(line_end);
+        let len = source[offset..span_end].chars().count().max(1);
+        let mark = if self.primary { "^" } else { "-" };
+
+        match self.label {
+            Some(label) => format!("{padding}{} {label}", mark.repeat(len)),
+            None => format!("{padding}{}", mark.repeat(len)),
+        }
+    }
+}
+
+/// The line of the source containing `start`.
+fn source_line(source: &str, start: Cursor) -> &str {
+    let offset = start.byte_offset.min(source.len());
+    let line_start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = source[offset..]
+        .find('\n')
+        .map_or(source.len(), |i| offset + i);
+
+    &source[line_start..line_end]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::linter::{AllowDenyRequire, Config, Linter};
+    use indoc::indoc;
+
+    fn render(source: &str) -> Vec<String> {
+        Linter::lint(Config::strict(), source)
+            .unwrap()
+            .iter()
+            .map(|lint| Text::new("test.hjson", source, lint).to_string())
+            .collect()
+    }
+
+    #[test]
+    fn underline() {
+        assert_eq!(
+            render("{\n  'foo': 3,\t\t\n}"),
+            [
+                indoc! {"
+                    test.hjson:2:11: deny(trailing-comma): trailing comma
+                      |
+                    2 |   'foo': 3,\t\t
+                      |           ^"},
+                indoc! {"
+                    test.hjson:2:12: deny(trailing-whitespace): trailing whitespace
+                      |
+                    2 |   'foo': 3,\t\t
+                      |            ^^"},
+            ]
+        );
+    }
+
+    #[test]
+    fn zero_length() {
+        assert_eq!(
+            render("{\n\t'x': 3\n\t'y': 5\n}"),
+            [indoc! {"
+                test.hjson:2:8: deny(missing-comma): missing comma
+                  |
+                2 | \t'x': 3
+                  | \t      ^"}]
+        );
+    }
+
+    #[test]
+    fn wide_gutter() {
+        let source = format!("{{{}'x': 3,\n}}", "\n".repeat(10));
+        assert_eq!(
+            render(&source),
+            [indoc! {"
+                test.hjson:11:7: deny(trailing-comma): trailing comma
+                   |
+                11 | 'x': 3,
+                   |       ^"}]
+        );
+    }
+
+    #[test]
+    fn labels() {
+        assert_eq!(
+            render("{\n  \"a\": 1,\n  \"b\": 2,\n  \"a\": 3\n}"),
+            [indoc! {r#"
+                test.hjson:4:3: deny(duplicate-key): duplicate key
+                  |
+                2 |   "a": 1,
+                  |   --- first defined here
+                ...
+                4 |   "a": 3
+                  |   ^^^
+                  |
+                  = note: parsers disagree on which value to use for a repeated key
+                  = help: remove or rename one of the keys"#}]
+        );
+
+        let source = "{ a: 1 }";
+        let config = Config {
+            root_braces: AllowDenyRequire::Deny,
+            ..Config::default()
+        };
+        let lints = Linter::lint(&config, source).unwrap();
+        assert_eq!(
+            Text::new("test.hjson", source, &lints[0]).to_string(),
+            indoc! {"
+                test.hjson:1:1: deny(implicit-braces): implicit braces
+                  |
+                1 | { a: 1 }
+                  | ^
+                  |        - closing brace here"}
+        );
+    }
+
+    #[test]
+    fn syntax_error() {
+        let source = "{\n  a 1\n}";
+        let error = Linter::lint(Config::strict(), source).unwrap_err();
+
+        assert_eq!(
+            Text::error("test.hjson", source, &error).to_string(),
+            indoc! {"
+                test.hjson:2:5: error(syntax-error): expected :, got unquoted string
+                  |
+                2 |   a 1
+                  |     ^"}
+        );
+    }
+}
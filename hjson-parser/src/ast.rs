@@ -0,0 +1,336 @@
+//! Typed views of a parsed tree.
+//!
+//! Each type wraps a [`SyntaxNode`] or [`SyntaxToken`] of the right kind and
+//! gives access to its parts. Parts may be missing from trees with syntax
+//! errors, so accessors return [`Option`]s and iterators skip anything which
+//! failed to parse.
+//!
+//! ```
+//! use hjson_parser::ast::{AstNode, File};
+//!
+//! let source = "a: 1\nb: [2, 3]";
+//! let (tree, _) = hjson_parser::parse(source);
+//! let file = File::new(source, &tree);
+//!
+//! let keys: Vec<&str> = file
+//!     .map()
+//!     .unwrap()
+//!     .members()
+//!     .filter_map(|mapping| mapping.key())
+//!     .map(|key| key.text())
+//!     .collect();
+//! assert_eq!(keys, ["a", "b"]);
+//! ```
+
+use std::ops::Range;
+
+use crate::parser::{KEY, VALUE};
+use crate::syntax::{SyntaxElement, SyntaxNode, SyntaxToken};
+use crate::token::TokenKind;
+use crate::tree::{Tree, TreeKind};
+
+/// A typed view of a [`SyntaxNode`].
+pub trait AstNode<'a>: Sized {
+    /// View `node` as this type, if it is the right kind.
+    fn cast(node: SyntaxNode<'a>) -> Option<Self>;
+
+    /// The untyped node this views.
+    fn syntax(&self) -> &SyntaxNode<'a>;
+
+    /// Byte range of this node in the source.
+    fn range(&self) -> Range<usize> {
+        self.syntax().range()
+    }
+
+    /// Source text of this node.
+    fn text(&self) -> &'a str {
+        self.syntax().text()
+    }
+}
+
+macro_rules! ast_node {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(Clone, Debug, PartialEq, Eq)]
+        pub struct $name<'a>(SyntaxNode<'a>);
+
+        impl<'a> AstNode<'a> for $name<'a> {
+            fn cast(node: SyntaxNode<'a>) -> Option<Self> {
+                (node.kind() == TreeKind::$name).then_some(Self(node))
+            }
+
+            fn syntax(&self) -> &SyntaxNode<'a> {
+                &self.0
+            }
+        }
+    };
+}
+
+ast_node! {
+    /// A whole Hjson file.
+    File
+}
+
+ast_node! {
+    /// A map, with or without braces.
+    Map
+}
+
+ast_node! {
+    /// A single `key: value` mapping in a map.
+    Mapping
+}
+
+ast_node! {
+    /// An array of values.
+    Array
+}
+
+/// The key of a mapping.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Key<'a>(SyntaxToken<'a>);
+
+/// A value in a mapping or array.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Value<'a> {
+    Map(Map<'a>),
+    Array(Array<'a>),
+    /// A string, number, Boolean, or null.
+    Scalar(SyntaxToken<'a>),
+}
+
+impl<'a> File<'a> {
+    /// A view of the whole `tree`, which must have been parsed from `source`.
+    pub fn new(source: &'a str, tree: &'a Tree) -> Self {
+        Self(SyntaxNode::new_root(source, tree))
+    }
+
+    /// The map at the root of the file, unless the file is empty.
+    pub fn map(&self) -> Option<Map<'a>> {
+        self.0.children().find_map(Map::cast)
+    }
+}
+
+impl<'a> Map<'a> {
+    /// The mappings in this map, in order.
+    pub fn members(&self) -> impl Iterator<Item = Mapping<'a>> {
+        self.0.children().filter_map(Mapping::cast)
+    }
+
+    /// The opening brace, if this map has braces.
+    pub fn open_brace(&self) -> Option<SyntaxToken<'a>> {
+        self.token(TokenKind::LBrace)
+    }
+
+    /// The closing brace, unless this map has no braces or is unclosed.
+    pub fn close_brace(&self) -> Option<SyntaxToken<'a>> {
+        self.token(TokenKind::RBrace)
+    }
+
+    /// The mapping containing this map, unless it's in an array or the root.
+    pub fn mapping(&self) -> Option<Mapping<'a>> {
+        self.0.parent().and_then(Mapping::cast)
+    }
+
+    fn token(&self, kind: TokenKind) -> Option<SyntaxToken<'a>> {
+        self.0
+            .children_with_tokens()
+            .filter_map(SyntaxElement::into_token)
+            .find(|token| token.kind() == kind)
+    }
+}
+
+impl<'a> Mapping<'a> {
+    /// The key of this mapping.
+    pub fn key(&self) -> Option<Key<'a>> {
+        self.0
+            .children_with_tokens()
+            .next()
+            .and_then(SyntaxElement::into_token)
+            .and_then(Key::cast)
+    }
+
+    /// The colon between the key and value.
+    pub fn colon(&self) -> Option<SyntaxToken<'a>> {
+        self.0
+            .children_with_tokens()
+            .filter_map(SyntaxElement::into_token)
+            .find(|token| token.kind() == TokenKind::Colon)
+    }
+
+    /// The value of this mapping.
+    pub fn value(&self) -> Option<Value<'a>> {
+        // Skip the key, which may look like a value.
+        self.0.children_with_tokens().skip(1).find_map(Value::cast)
+    }
+
+    /// The map containing this mapping.
+    pub fn map(&self) -> Option<Map<'a>> {
+        self.0.parent().and_then(Map::cast)
+    }
+}
+
+impl<'a> Array<'a> {
+    /// The values in this array, in order.
+    pub fn values(&self) -> impl Iterator<Item = Value<'a>> {
+        self.0.children_with_tokens().filter_map(Value::cast)
+    }
+
+    /// The mapping containing this array, unless it's in another array.
+    pub fn mapping(&self) -> Option<Mapping<'a>> {
+        self.0.parent().and_then(Mapping::cast)
+    }
+}
+
+impl<'a> Key<'a> {
+    /// View `token` as a key, if it is a kind of token keys can be.
+    pub fn cast(token: SyntaxToken<'a>) -> Option<Self> {
+        KEY.contains(&token.kind()).then_some(Self(token))
+    }
+
+    /// The untyped token this views.
+    pub fn syntax(&self) -> &SyntaxToken<'a> {
+        &self.0
+    }
+
+    /// The kind of string this key is.
+    pub fn kind(&self) -> TokenKind {
+        self.0.kind()
+    }
+
+    /// Byte range of this key in the source.
+    pub fn range(&self) -> Range<usize> {
+        self.0.range()
+    }
+
+    /// Source text of this key, including any quotes.
+    pub fn text(&self) -> &'a str {
+        self.0.text()
+    }
+
+    /// The mapping this is the key of.
+    pub fn mapping(&self) -> Option<Mapping<'a>> {
+        Mapping::cast(self.0.parent())
+    }
+}
+
+impl<'a> Value<'a> {
+    /// View `element` as a value, if it is a map, array, or scalar.
+    pub fn cast(element: SyntaxElement<'a>) -> Option<Self> {
+        match element {
+            SyntaxElement::Node(node) => match node.kind() {
+                TreeKind::Map => Some(Self::Map(Map(node))),
+                TreeKind::Array => Some(Self::Array(Array(node))),
+                _ => None,
+            },
+            SyntaxElement::Token(token) => match token.kind() {
+                TokenKind::LBrace | TokenKind::LBracket => None,
+                kind if VALUE.contains(&kind) => Some(Self::Scalar(token)),
+                _ => None,
+            },
+        }
+    }
+
+    /// Byte range of this value in the source.
+    pub fn range(&self) -> Range<usize> {
+        match self {
+            Self::Map(map) => map.range(),
+            Self::Array(array) => array.range(),
+            Self::Scalar(token) => token.range(),
+        }
+    }
+
+    /// Source text of this value.
+    pub fn text(&self) -> &'a str {
+        match self {
+            Self::Map(map) => map.text(),
+            Self::Array(array) => array.text(),
+            Self::Scalar(token) => token.text(),
+        }
+    }
+
+    /// The node containing this value: a mapping or an array.
+    pub fn parent(&self) -> Option<SyntaxNode<'a>> {
+        match self {
+            Self::Map(map) => map.syntax().parent(),
+            Self::Array(array) => array.syntax().parent(),
+            Self::Scalar(token) => Some(token.parent()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parse;
+    use indoc::indoc;
+
+    #[test]
+    fn members() {
+        let source = indoc! {"
+            # comment
+            a: 1
+            'b': [true, { c: null }]
+            d: {}
+        "};
+        let (tree, _) = parse(source);
+        let file = File::new(source, &tree);
+        let map = file.map().unwrap();
+
+        assert_eq!(map.open_brace(), None);
+        let members: Vec<_> = map.members().collect();
+        assert_eq!(members.len(), 3);
+
+        let key = members[1].key().unwrap();
+        assert_eq!(key.text(), "'b'");
+        assert_eq!(key.kind(), TokenKind::TextSingle);
+        assert_eq!(key.range(), 15..18);
+        assert_eq!(key.mapping().as_ref(), Some(&members[1]));
+        assert_eq!(members[1].colon().unwrap().range(), 18..19);
+
+        let Some(Value::Array(array)) = members[1].value() else {
+            panic!("expected an array");
+        };
+        assert_eq!(array.mapping().as_ref(), Some(&members[1]));
+
+        let values: Vec<_> = array.values().collect();
+        assert!(matches!(&values[0], Value::Scalar(token) if token.text() == "true"));
+        let Value::Map(inner) = &values[1] else {
+            panic!("expected a map");
+        };
+        assert_eq!(inner.text(), "{ c: null }");
+        assert_eq!(inner.mapping(), None);
+        assert_eq!(inner.members().next().unwrap().map().as_ref(), Some(inner));
+
+        let Some(Value::Map(empty)) = members[2].value() else {
+            panic!("expected a map");
+        };
+        assert_eq!(empty.members().count(), 0);
+        assert_eq!(empty.close_brace().unwrap().range(), 44..45);
+        assert_eq!(
+            empty
+                .syntax()
+                .parent()
+                .unwrap()
+                .prev_sibling()
+                .and_then(Mapping::cast),
+            Some(members[1].clone())
+        );
+    }
+
+    #[test]
+    fn with_errors() {
+        let source = "{ a 1, b: , c: 2";
+        let (tree, errors) = parse(source);
+        let map = File::new(source, &tree).map().unwrap();
+
+        assert!(!errors.is_empty());
+        assert_eq!(map.close_brace(), None);
+
+        let values: Vec<_> = map
+            .members()
+            .map(|mapping| mapping.value().map(|value| value.text()))
+            .collect();
+        assert_eq!(values, [Some("1"), None, Some("2")]);
+    }
+}
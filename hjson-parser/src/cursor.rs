@@ -0,0 +1,57 @@
+/// A position in the source.
+///
+/// Lines and columns both count from 1, and columns count bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cursor {
+    pub line: usize,
+    pub column: usize,
+    pub byte_offset: usize,
+}
+
+impl Default for Cursor {
+    fn default() -> Self {
+        Cursor {
+            line: 1,
+            column: 1,
+            byte_offset: 0,
+        }
+    }
+}
+
+impl Cursor {
+    pub fn new(line: usize, column: usize, byte_offset: usize) -> Self {
+        Cursor {
+            line,
+            column,
+            byte_offset,
+        }
+    }
+
+    /// The cursor at a byte offset into `source`.
+    pub fn at(source: &str, byte_offset: usize) -> Self {
+        let before = &source[..byte_offset];
+        let line_start = before.rfind('\n').map_or(0, |x| x + 1);
+
+        Cursor {
+            line: before.matches('\n').count() + 1,
+            column: byte_offset - line_start + 1,
+            byte_offset,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn at() {
+        let source = "a: 1\nbc: 2\n";
+
+        assert_eq!(Cursor::at(source, 0), Cursor::default());
+        assert_eq!(Cursor::at(source, 3), Cursor::new(1, 4, 3));
+        assert_eq!(Cursor::at(source, 5), Cursor::new(2, 1, 5));
+        assert_eq!(Cursor::at(source, 9), Cursor::new(2, 5, 9));
+        assert_eq!(Cursor::at(source, 11), Cursor::new(3, 1, 11));
+    }
+}
@@ -0,0 +1,564 @@
+//! Deserializing Rust data structures from Hjson with [`serde`].
+//!
+//! Documents are first decoded into [`Value`]s, so every error can point at
+//! the value it came from using the same [`Cursor`] the linter reports. Values
+//! can also be deserialized as [`Spanned`](crate::Spanned) to keep their
+//! positions.
+//!
+//! ```
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize)]
+//! struct Config {
+//!     name: String,
+//!     ports: Vec<u16>,
+//! }
+//!
+//! let config: Config = hjson_parser::from_str("name: server\nports: [80, 443]").unwrap();
+//! assert_eq!(config.name, "server");
+//! assert_eq!(config.ports, [80, 443]);
+//! ```
+
+use std::error;
+use std::fmt::{self, Display};
+use std::ops::Range;
+
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess,
+    VariantAccess, Visitor,
+};
+use serde::forward_to_deserialize_any;
+
+use crate::cursor::Cursor;
+use crate::spanned;
+use crate::value::{DecodeError, Key, Member, Number, Value, ValueKind};
+
+/// Deserialize an instance of `T` from an Hjson document.
+pub fn from_str<T: DeserializeOwned>(input: &str) -> Result<T, Error> {
+    let value = crate::parse_value(input).map_err(|err| Error::decode(input, err))?;
+    from_value(input, &value)
+}
+
+/// Deserialize an instance of `T` from a value decoded from `source`.
+pub fn from_value<T: DeserializeOwned>(source: &str, value: &Value) -> Result<T, Error> {
+    T::deserialize(Deserializer::new(source, value))
+}
+
+/// An error deserializing a document, with the position it was found at.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Error {
+    message: String,
+    span: Option<(Range<usize>, Cursor)>,
+}
+
+impl Error {
+    /// Description of the error, without its position.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Byte range of the source the error was found at.
+    pub fn span(&self) -> Option<Range<usize>> {
+        self.span.as_ref().map(|(span, _)| span.clone())
+    }
+
+    /// Position of the start of the span the error was found at.
+    pub fn cursor(&self) -> Option<Cursor> {
+        self.span.as_ref().map(|(_, cursor)| *cursor)
+    }
+
+    /// An error from decoding a document, before it could be deserialized.
+    fn decode(source: &str, err: DecodeError) -> Self {
+        let span = match &err {
+            DecodeError::Syntax(errors) => errors.first().map(|error| error.range.clone()),
+            DecodeError::InvalidEscape(range) => Some(range.clone()),
+        };
+
+        let err = Error {
+            message: err.to_string(),
+            span: None,
+        };
+        match span {
+            Some(span) => err.at(source, span),
+            None => err,
+        }
+    }
+
+    /// Give this error a position, unless it already has a more precise one.
+    fn at(mut self, source: &str, span: Range<usize>) -> Self {
+        if self.span.is_none() {
+            let cursor = Cursor::at(source, span.start);
+            self.span = Some((span, cursor));
+        }
+        self
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.cursor() {
+            Some(cursor) => write!(f, "{}:{}: {}", cursor.line, cursor.column, self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+impl de::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Error {
+            message: msg.to_string(),
+            span: None,
+        }
+    }
+}
+
+/// Deserializer for a single [`Value`].
+#[derive(Clone, Copy)]
+pub struct Deserializer<'a> {
+    source: &'a str,
+    value: &'a Value,
+}
+
+impl<'a> Deserializer<'a> {
+    /// A deserializer for `value`, which was decoded from `source`.
+    pub fn new(source: &'a str, value: &'a Value) -> Self {
+        Self { source, value }
+    }
+
+    /// Point any error from deserializing this value at it.
+    fn locate<T>(&self, result: Result<T, Error>) -> Result<T, Error> {
+        result.map_err(|err| err.at(self.source, self.value.span.clone()))
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Deserializer<'_> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let result = match &self.value.kind {
+            ValueKind::Null => visitor.visit_unit(),
+            ValueKind::Bool(b) => visitor.visit_bool(*b),
+            ValueKind::Number(Number::U64(n)) => visitor.visit_u64(*n),
+            ValueKind::Number(Number::I64(n)) => visitor.visit_i64(*n),
+            ValueKind::Number(Number::F64(n)) => visitor.visit_f64(*n),
+            ValueKind::String(s) => visitor.visit_str(s),
+            ValueKind::Array(values) => visitor.visit_seq(Seq {
+                source: self.source,
+                values: values.iter(),
+            }),
+            ValueKind::Map(map) => visitor.visit_map(Map {
+                source: self.source,
+                members: map.members.iter(),
+                value: None,
+            }),
+        };
+
+        self.locate(result)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let result = match self.value.kind {
+            ValueKind::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        };
+
+        self.locate(result)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let result = visitor.visit_newtype_struct(self);
+        self.locate(result)
+    }
+
+    /// Enums are either strings for unit variants, or maps with a single
+    /// member whose key is the variant.
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let result = match &self.value.kind {
+            ValueKind::String(variant) => visitor.visit_enum(variant.as_str().into_deserializer()),
+            ValueKind::Map(map) if map.len() == 1 => visitor.visit_enum(Enum {
+                source: self.source,
+                member: &map.members[0],
+            }),
+            _ => Err(de::Error::invalid_type(
+                self.unexpected(),
+                &"string or map with one key",
+            )),
+        };
+
+        self.locate(result)
+    }
+
+    /// [`Spanned`](crate::Spanned) values are deserialized from their
+    /// position as well as the value itself.
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        if name == spanned::NAME && fields == spanned::FIELDS {
+            return visitor.visit_map(SpannedAccess {
+                deserializer: self,
+                field: 0,
+            });
+        }
+
+        self.deserialize_any(visitor)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map
+        identifier ignored_any
+    }
+}
+
+impl Deserializer<'_> {
+    /// What the value is, for errors.
+    fn unexpected(&self) -> de::Unexpected<'_> {
+        match &self.value.kind {
+            ValueKind::Null => de::Unexpected::Unit,
+            ValueKind::Bool(b) => de::Unexpected::Bool(*b),
+            ValueKind::Number(Number::U64(n)) => de::Unexpected::Unsigned(*n),
+            ValueKind::Number(Number::I64(n)) => de::Unexpected::Signed(*n),
+            ValueKind::Number(Number::F64(n)) => de::Unexpected::Float(*n),
+            ValueKind::String(s) => de::Unexpected::Str(s),
+            ValueKind::Array(_) => de::Unexpected::Seq,
+            ValueKind::Map(_) => de::Unexpected::Map,
+        }
+    }
+}
+
+/// Access to the values of an array.
+struct Seq<'a> {
+    source: &'a str,
+    values: std::slice::Iter<'a, Value>,
+}
+
+impl<'de> SeqAccess<'de> for Seq<'_> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        match self.values.next() {
+            Some(value) => seed
+                .deserialize(Deserializer::new(self.source, value))
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.values.len())
+    }
+}
+
+/// Access to the members of a map.
+struct Map<'a> {
+    source: &'a str,
+    members: std::slice::Iter<'a, Member>,
+    /// The value of the member whose key was just deserialized.
+    value: Option<&'a Value>,
+}
+
+impl<'de> MapAccess<'de> for Map<'_> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        let Some(member) = self.members.next() else {
+            return Ok(None);
+        };
+
+        self.value = Some(&member.value);
+        seed.deserialize(KeyDeserializer {
+            source: self.source,
+            key: &member.key,
+        })
+        .map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        let value = self.value.take().expect("value requested before key");
+        seed.deserialize(Deserializer::new(self.source, value))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.members.len())
+    }
+}
+
+/// Access to the fields of a [`Spanned`](crate::Spanned) value.
+struct SpannedAccess<'a> {
+    deserializer: Deserializer<'a>,
+    /// Index of the next field in [`spanned::FIELDS`].
+    field: usize,
+}
+
+impl<'de> MapAccess<'de> for SpannedAccess<'_> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        match spanned::FIELDS.get(self.field) {
+            Some(field) => seed.deserialize(field.into_deserializer()).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        let Deserializer { source, value } = self.deserializer;
+        let cursor = Cursor::at(source, value.span.start);
+
+        let field = spanned::FIELDS[self.field];
+        self.field += 1;
+
+        match field {
+            spanned::START => seed.deserialize(value.span.start.into_deserializer()),
+            spanned::END => seed.deserialize(value.span.end.into_deserializer()),
+            spanned::LINE => seed.deserialize(cursor.line.into_deserializer()),
+            spanned::COLUMN => seed.deserialize(cursor.column.into_deserializer()),
+            _ => seed.deserialize(self.deserializer),
+        }
+    }
+}
+
+/// Deserializer for the key of a map member, which is always a string.
+struct KeyDeserializer<'a> {
+    source: &'a str,
+    key: &'a Key,
+}
+
+/// Deserialize keys which were written from other scalars, like integer keys,
+/// by parsing them.
+macro_rules! deserialize_parsed_key {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                match self.key.name.parse() {
+                    Ok(parsed) => visitor
+                        .$visit(parsed)
+                        .map_err(|err: Error| err.at(self.source, self.key.span.clone())),
+                    Err(_) => self.deserialize_any(visitor),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for KeyDeserializer<'_> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor
+            .visit_str(&self.key.name)
+            .map_err(|err: Error| err.at(self.source, self.key.span.clone()))
+    }
+
+    deserialize_parsed_key! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+    }
+
+    forward_to_deserialize_any! {
+        f32 f64 char str string bytes byte_buf option unit unit_struct
+        newtype_struct seq tuple tuple_struct map struct enum identifier
+        ignored_any
+    }
+}
+
+/// Access to the variant of an enum written as a map with one member.
+struct Enum<'a> {
+    source: &'a str,
+    member: &'a Member,
+}
+
+impl<'de, 'a> EnumAccess<'de> for Enum<'a> {
+    type Error = Error;
+    type Variant = Deserializer<'a>;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self::Variant), Error> {
+        let variant = seed.deserialize(KeyDeserializer {
+            source: self.source,
+            key: &self.member.key,
+        })?;
+
+        Ok((variant, Deserializer::new(self.source, &self.member.value)))
+    }
+}
+
+impl<'de> VariantAccess<'de> for Deserializer<'_> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        de::Deserialize::deserialize(self)
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_seq(self, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_map(self, visitor)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use indoc::indoc;
+    use serde::Deserialize;
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(deny_unknown_fields)]
+    struct Config {
+        name: String,
+        port: u16,
+        debug: Option<bool>,
+        ratio: f64,
+        tags: Vec<String>,
+        mode: Mode,
+        limits: BTreeMap<String, i64>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename_all = "kebab-case")]
+    enum Mode {
+        Fast,
+        Slow { delay: u32 },
+    }
+
+    #[test]
+    fn structs() {
+        let config: Config = from_str(indoc! {"
+            # Comments are fine.
+            name: my server
+            port: 8080
+            debug: null
+            ratio: 0.5
+            tags: ['a', 'b c']
+            mode: { slow: { delay: 10 } }
+            limits: {
+              min: -1
+              max: 100
+            }
+        "})
+        .unwrap();
+
+        assert_eq!(
+            config,
+            Config {
+                name: String::from("my server"),
+                port: 8080,
+                debug: None,
+                ratio: 0.5,
+                tags: Vec::from([String::from("a"), String::from("b c")]),
+                mode: Mode::Slow { delay: 10 },
+                limits: BTreeMap::from([(String::from("min"), -1), (String::from("max"), 100)]),
+            }
+        );
+    }
+
+    #[test]
+    fn enums() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Modes {
+            a: Mode,
+            b: Mode,
+        }
+
+        let modes: Modes = from_str("a: fast\nb: { slow: { delay: 1 } }").unwrap();
+        assert_eq!(
+            modes,
+            Modes {
+                a: Mode::Fast,
+                b: Mode::Slow { delay: 1 }
+            }
+        );
+    }
+
+    #[test]
+    fn parsed_keys() {
+        let map: BTreeMap<u8, BTreeMap<bool, String>> =
+            from_str("1: { true: 'yes' }\n2: {}").unwrap();
+        assert_eq!(
+            map,
+            BTreeMap::from([
+                (1, BTreeMap::from([(true, String::from("yes"))])),
+                (2, BTreeMap::new()),
+            ])
+        );
+
+        let err = from_str::<BTreeMap<u8, u8>>("a: 1").unwrap_err();
+        assert_eq!(err.cursor(), Some(Cursor::new(1, 1, 0)));
+    }
+
+    #[test]
+    fn errors() {
+        let error = |input| from_str::<Config>(input).unwrap_err();
+
+        let err = error(indoc! {"
+            name: server
+            port: 70000
+        "});
+        assert_eq!(
+            err.to_string(),
+            "2:7: invalid value: integer `70000`, expected u16"
+        );
+        assert_eq!(err.span(), Some(19..24));
+
+        let err = error("name: server\nport: 80\ncolour: red\n");
+        assert_eq!(err.cursor(), Some(Cursor::new(3, 1, 22)));
+        assert!(err.message().starts_with("unknown field `colour`"));
+
+        let err = error("name: server\ntags: ['a', 2]\n");
+        assert_eq!(err.cursor(), Some(Cursor::new(2, 13, 25)));
+
+        let err = error("{\n  name: server\n}");
+        assert_eq!(err.to_string(), "1:1: missing field `port`");
+
+        let err = error("name: server\nport: [80,, 81]\n");
+        assert_eq!(err.to_string(), "2:11: expected value, got ,");
+
+        let err = error("name: \"\\x\"");
+        assert_eq!(err.to_string(), "1:8: invalid escape sequence");
+    }
+}
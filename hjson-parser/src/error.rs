@@ -0,0 +1,51 @@
+use std::error;
+use std::fmt::{self, Display};
+use std::ops::Range;
+
+use crate::parser::{KEY, MAP, VALUE};
+use crate::token::TokenKind;
+
+/// A syntax error found while parsing.
+///
+/// The parser recovers from errors, so there may be many of these for a
+/// single file. Each is recorded where the parser was when it found a token
+/// it did not expect.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Error {
+    /// Byte range of the token found. This is empty at the end of the input.
+    pub range: Range<usize>,
+    /// Kinds of token which would have been accepted instead.
+    pub expected: Vec<TokenKind>,
+    /// Kind of token found.
+    pub got: TokenKind,
+}
+
+impl Error {
+    /// Describe the expected tokens, e.g. `value` or `:`.
+    pub fn describe_expected(&self) -> String {
+        let expected = self.expected.as_slice();
+
+        let name = match expected {
+            [TokenKind::Eof] => "end of file",
+            [kind] => return kind.to_string(),
+            _ if expected == KEY => "key",
+            _ if expected == VALUE => "value",
+            _ if expected == MAP => "map",
+            [TokenKind::Comma, TokenKind::NewLine] => "comma or new-line",
+            _ => {
+                let kinds: Vec<String> = expected.iter().map(ToString::to_string).collect();
+                return format!("one of {}", kinds.join(", "));
+            }
+        };
+
+        name.to_string()
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "expected {}, got {}", self.describe_expected(), self.got)
+    }
+}
+
+impl error::Error for Error {}
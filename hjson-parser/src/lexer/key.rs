@@ -1,3 +1,4 @@
+use super::text::is_escaped;
 use crate::token::{Token, TokenKind};
 
 /// Parse text in the "key" context, meaning as if it appeared as a key in a
@@ -9,12 +10,12 @@ pub fn parse(input: &str) -> Option<Token> {
     if input.starts_with('\'') {
         let (idx, _) = input
             .char_indices()
-            .find(|(i, c)| *i != 0 && *c == '\'' && !input[..*i].ends_with('\\'))?;
+            .find(|(i, c)| *i != 0 && *c == '\'' && !is_escaped(input, *i))?;
         Some(TokenKind::TextSingle.with_len(idx + 1))
     } else if input.starts_with('"') {
         let (idx, _) = input
             .char_indices()
-            .find(|(i, c)| *i != 0 && *c == '"' && !input[..*i].ends_with('\\'))?;
+            .find(|(i, c)| *i != 0 && *c == '"' && !is_escaped(input, *i))?;
         Some(TokenKind::TextDouble.with_len(idx + 1))
     } else {
         let terminators = [',', ':', '[', ']', '{', '}'];
@@ -36,6 +37,7 @@ mod test {
         assert_eq!(parse("'foo'"), Some(TokenKind::TextSingle.with_len(5)));
         assert_eq!(parse("'a'b"), Some(TokenKind::TextSingle.with_len(3)));
         assert_eq!(parse(r#"'a\'b'"#), Some(TokenKind::TextSingle.with_len(6)));
+        assert_eq!(parse(r#"'a\\'"#), Some(TokenKind::TextSingle.with_len(5)));
     }
 
     #[test]
@@ -44,6 +46,7 @@ mod test {
         assert_eq!(parse(r#""foo""#), Some(TokenKind::TextDouble.with_len(5)));
         assert_eq!(parse(r#""a"b"#), Some(TokenKind::TextDouble.with_len(3)));
         assert_eq!(parse(r#""a\"b""#), Some(TokenKind::TextDouble.with_len(6)));
+        assert_eq!(parse(r#""a\\""#), Some(TokenKind::TextDouble.with_len(5)));
     }
 
     #[test]
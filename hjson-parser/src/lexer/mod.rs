@@ -15,29 +15,27 @@ pub mod symbol;
 pub mod text;
 pub mod whitespace;
 
-use std::iter;
-
-use crate::token::Token;
+use crate::token::{Token, TokenKind};
 
 /// Return the next token from the given input with the given context.
 pub fn token(input: &str, context: &Context) -> Token {
-    let parsers = [
-        whitespace::parse,
-        comment::parse,
-        keyword::parse,
-        number::parse,
-        symbol::parse,
-    ]
-    .into_iter();
-
-    let text_parser = match context {
-        Context::Key => key::parse,
-        Context::Value => text::parse,
+    // Keys are always text, even if they look like numbers or keywords.
+    let parsers: &[fn(&str) -> Option<Token>] = match context {
+        Context::Key => &[whitespace::parse, comment::parse, symbol::parse, key::parse],
+        Context::Value => &[
+            whitespace::parse,
+            comment::parse,
+            keyword::parse,
+            number::parse,
+            symbol::parse,
+            text::parse,
+        ],
     };
 
-    let mut parsers = parsers.chain(iter::once(text_parser));
-
-    parsers.find_map(|p| p(input)).expect("no parser matched")
+    parsers.iter().find_map(|p| p(input)).unwrap_or_else(|| {
+        let len = input.find('\n').unwrap_or(input.len());
+        TokenKind::Error.with_len(len)
+    })
 }
 
 /// The context in which to perform lexical analysis.
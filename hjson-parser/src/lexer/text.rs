@@ -19,12 +19,12 @@ pub fn parse(input: &str) -> Option<Token> {
     } else if input.starts_with('\'') {
         let (idx, _) = input
             .char_indices()
-            .find(|(i, c)| *i != 0 && *c == '\'' && !input[..*i].ends_with('\\'))?;
+            .find(|(i, c)| *i != 0 && *c == '\'' && !is_escaped(input, *i))?;
         Some(TokenKind::TextSingle.with_len(idx + 1))
     } else if input.starts_with('"') {
         let (idx, _) = input
             .char_indices()
-            .find(|(i, c)| *i != 0 && *c == '"' && !input[..*i].ends_with('\\'))?;
+            .find(|(i, c)| *i != 0 && *c == '"' && !is_escaped(input, *i))?;
         Some(TokenKind::TextDouble.with_len(idx + 1))
     } else {
         let eol = input.find('\n').unwrap_or(input.len());
@@ -33,6 +33,12 @@ pub fn parse(input: &str) -> Option<Token> {
     }
 }
 
+/// Whether the character at byte `i` is escaped, by an odd number of
+/// backslashes before it.
+pub(super) fn is_escaped(input: &str, i: usize) -> bool {
+    input[..i].bytes().rev().take_while(|&b| b == b'\\').count() % 2 == 1
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -44,6 +50,11 @@ mod test {
         assert_eq!(parse("'foo'"), Some(TokenKind::TextSingle.with_len(5)));
         assert_eq!(parse("'a'b"), Some(TokenKind::TextSingle.with_len(3)));
         assert_eq!(parse(r#"'a\'b'"#), Some(TokenKind::TextSingle.with_len(6)));
+        assert_eq!(parse(r#"'a\\'"#), Some(TokenKind::TextSingle.with_len(5)));
+        assert_eq!(
+            parse(r#"'a\\\'b'"#),
+            Some(TokenKind::TextSingle.with_len(8))
+        );
     }
 
     #[test]
@@ -52,6 +63,11 @@ mod test {
         assert_eq!(parse(r#""foo""#), Some(TokenKind::TextDouble.with_len(5)));
         assert_eq!(parse(r#""a"b"#), Some(TokenKind::TextDouble.with_len(3)));
         assert_eq!(parse(r#""a\"b""#), Some(TokenKind::TextDouble.with_len(6)));
+        assert_eq!(parse(r#""a\\""#), Some(TokenKind::TextDouble.with_len(5)));
+        assert_eq!(
+            parse(r#""a\\\"b""#),
+            Some(TokenKind::TextDouble.with_len(8))
+        );
     }
 
     #[test]
@@ -1,12 +1,44 @@
+pub mod ast;
+mod cursor;
+pub mod de;
+mod error;
 mod lexer;
 mod parser;
+pub mod ser;
+mod spanned;
+mod syntax;
 mod token;
 mod tree;
+pub mod value;
 
+pub use cursor::Cursor;
+pub use de::from_str;
+pub use error::Error;
 use parser::Parser;
-pub use tree::Tree;
+pub use ser::{to_string, to_writer};
+pub use spanned::Spanned;
+pub use syntax::{SyntaxElement, SyntaxNode, SyntaxToken};
+pub use token::{Token, TokenKind};
+pub use tree::{Child, Tree, TreeKind};
+pub use value::{DecodeError, Value};
 
-pub fn parse(input: &str) -> Tree {
-    let events = Parser::parse(input);
-    Tree::build(events)
+/// Parse an Hjson file into a [`Tree`], along with any syntax [`Error`]s.
+///
+/// Parsing recovers from errors, so a tree is always returned. Anything which
+/// could not be parsed is kept in [`TreeKind::ErrorTree`]s.
+pub fn parse(input: &str) -> (Tree, Vec<Error>) {
+    let (events, errors) = Parser::parse(input);
+    (Tree::build(events), errors)
+}
+
+/// Parse an Hjson file and decode it into a [`Value`].
+///
+/// Unlike [`parse`], this fails if there are any syntax errors.
+pub fn parse_value(input: &str) -> Result<Value, DecodeError> {
+    let (tree, errors) = parse(input);
+    if !errors.is_empty() {
+        return Err(DecodeError::Syntax(errors));
+    }
+
+    Value::from_file(&ast::File::new(input, &tree))
 }
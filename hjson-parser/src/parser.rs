@@ -12,6 +12,7 @@
 //! that are incorrectly specified. I don't intend to make a full-blown LSP
 //! server or anything just for Hjson.
 
+use crate::error::Error;
 use crate::lexer::{self, Context};
 use crate::token::{Token, TokenKind};
 use crate::tree::TreeKind;
@@ -19,13 +20,16 @@ use crate::tree::TreeKind;
 /// Hjson parser which generates [`Event`]s from an input string.
 ///
 /// These events describe a tree structure, but are collected linearly during
-/// parsing for simplicity.
+/// parsing for simplicity. Syntax [`Error`]s are collected alongside them.
 #[derive(Clone, Debug)]
 pub struct Parser<'a> {
     input: &'a str,
+    /// Byte offset of `input` in the original input.
+    offset: usize,
     current: Token,
     context: Vec<Context>,
     events: Vec<Event>,
+    errors: Vec<Error>,
 }
 
 /// Parsing events.
@@ -51,18 +55,21 @@ struct MarkOpened {
 }
 
 impl Parser<'_> {
-    /// Parse the given Hjson file, returning a stream of events.
-    pub fn parse(input: &str) -> Vec<Event> {
+    /// Parse the given Hjson file, returning a stream of events and any
+    /// syntax errors.
+    pub fn parse(input: &str) -> (Vec<Event>, Vec<Error>) {
         let mut parser = Parser {
             input,
+            offset: 0,
             current: lexer::token(input, &Context::Key),
             context: Vec::from([Context::Key]),
             events: Vec::new(),
+            errors: Vec::new(),
         };
 
         file(&mut parser);
 
-        parser.events
+        (parser.events, parser.errors)
     }
 
     /// Open a new tree here.
@@ -92,6 +99,7 @@ impl Parser<'_> {
         let token = self.current;
 
         self.input = &self.input[token.len..];
+        self.offset += token.len;
         self.events.push(Event::Advance { token });
 
         self.relex_token();
@@ -152,30 +160,36 @@ impl Parser<'_> {
             return;
         }
 
-        // TODO: error reporting.
-        eprintln!("expected {kind:?}, got {:?}", self.current.kind);
+        self.error(&[kind]);
     }
 
     /// Expect some token matching one of the given kinds of token.
-    ///
-    /// The given `name` will be used for the expected token in errors.
-    fn expect_some(&mut self, kinds: &[TokenKind], name: &str) {
+    fn expect_some(&mut self, kinds: &[TokenKind]) {
         if self.eat_any(kinds) {
             return;
         }
 
-        // TODO: error reporting.
-        eprintln!("expected {name}");
+        self.error(kinds);
     }
 
-    /// Advance to the next token and generate an error.
-    fn advance_with_error(&mut self, error: &str) {
+    /// Advance to the next token and generate an error, having expected one
+    /// of the given kinds of token instead.
+    fn advance_with_error(&mut self, expected: &[TokenKind]) {
+        self.error(expected);
+
         let mark = self.open();
         self.advance();
         self.close(mark, TreeKind::ErrorTree);
+    }
 
-        //TODO: error reporting.
-        eprintln!("{error}");
+    /// Record an error for the current token, having expected one of the
+    /// given kinds of token instead.
+    fn error(&mut self, expected: &[TokenKind]) {
+        self.errors.push(Error {
+            range: self.offset..self.offset + self.current.len,
+            expected: expected.to_vec(),
+            got: self.current.kind,
+        });
     }
 
     /// Re-lex (lexically analyze) the current token from the input.
@@ -221,14 +235,14 @@ const SEPARATOR: &[TokenKind; 5] = &[
 ];
 
 /// Token kinds that can represent keys of a mapping.
-const KEY: &[TokenKind; 3] = &[
+pub(crate) const KEY: &[TokenKind] = &[
     TokenKind::TextSingle,
     TokenKind::TextDouble,
     TokenKind::TextUnquoted,
 ];
 
 /// Token kinds that can represent the start of a value.
-const VALUE: &[TokenKind; 10] = &[
+pub(crate) const VALUE: &[TokenKind] = &[
     TokenKind::Bool,
     TokenKind::Null,
     TokenKind::Integer,
@@ -242,23 +256,36 @@ const VALUE: &[TokenKind; 10] = &[
     TokenKind::LBracket,
 ];
 
+/// Token kinds that can represent the start of a map.
+pub(crate) const MAP: &[TokenKind] = &[
+    TokenKind::LBrace,
+    TokenKind::TextSingle,
+    TokenKind::TextDouble,
+    TokenKind::TextUnquoted,
+];
+
 /// Parse a full Hjson file.
 fn file(p: &mut Parser) {
     let mark = p.open();
 
     p.eat_all(SEPARATOR);
 
+    // Files with nothing but comments are empty maps.
     if p.at(TokenKind::LBrace) {
         map(p, true);
     } else if p.at_any(KEY) {
         map(p, false)
-    } else {
-        p.advance_with_error("expected map")
+    } else if !p.eof() {
+        p.advance_with_error(MAP)
     }
 
-    p.close(mark, TreeKind::File);
+    p.eat_all(SEPARATOR);
+    while !p.eof() {
+        p.advance_with_error(&[TokenKind::Eof]);
+        p.eat_all(SEPARATOR);
+    }
 
-    // TODO: expect EOF here?
+    p.close(mark, TreeKind::File);
 }
 
 /// Parse a full map, optionally requiring that it has surrounding braces.
@@ -269,16 +296,19 @@ fn map(p: &mut Parser, braces: bool) {
         p.expect(TokenKind::LBrace);
     }
 
+    // Members start with keys, even when the map itself is a value.
+    p.push_context(Context::Key);
+
     while !p.eof() {
         p.eat_all(SEPARATOR);
 
+        if p.eof() || (p.at(TokenKind::RBrace) && braces) {
+            break;
+        }
+
         if p.at(TokenKind::RBrace) {
-            if braces {
-                break;
-            } else {
-                p.advance_with_error("unexpected close brace");
-                continue;
-            }
+            p.advance_with_error(KEY);
+            continue;
         }
 
         if p.at_any(KEY) {
@@ -286,10 +316,10 @@ fn map(p: &mut Parser, braces: bool) {
             p.eat_all(DECO);
 
             if !p.at(TokenKind::RBrace) && !p.eof() {
-                p.expect_some(&[TokenKind::Comma, TokenKind::NewLine], "comma or new-line");
+                p.expect_some(&[TokenKind::Comma, TokenKind::NewLine]);
             }
         } else {
-            p.advance_with_error("expected mapping");
+            p.advance_with_error(KEY);
         }
     }
 
@@ -297,6 +327,7 @@ fn map(p: &mut Parser, braces: bool) {
         p.expect(TokenKind::RBrace);
     }
 
+    p.pop_context();
     p.close(mark, TreeKind::Map);
 }
 
@@ -306,7 +337,7 @@ fn mapping(p: &mut Parser) {
 
     let mark = p.open();
 
-    p.expect_some(KEY, "key");
+    p.expect_some(KEY);
     p.eat_all(SEPARATOR);
     p.expect(TokenKind::Colon);
     p.eat_all(SEPARATOR);
@@ -314,7 +345,7 @@ fn mapping(p: &mut Parser) {
     if p.at_any(VALUE) {
         value(p);
     } else {
-        p.advance_with_error("expected value");
+        p.advance_with_error(VALUE);
     }
 
     p.close(mark, TreeKind::Mapping);
@@ -330,7 +361,7 @@ fn array(p: &mut Parser) {
     while !p.eof() {
         p.eat_all(SEPARATOR);
 
-        if p.at(TokenKind::RBracket) {
+        if p.eof() || p.at(TokenKind::RBracket) {
             break;
         }
 
@@ -339,10 +370,10 @@ fn array(p: &mut Parser) {
             p.eat_all(DECO);
 
             if !p.at(TokenKind::RBracket) && !p.eof() {
-                p.expect_some(&[TokenKind::Comma, TokenKind::NewLine], "comma or new-line");
+                p.expect_some(&[TokenKind::Comma, TokenKind::NewLine]);
             }
         } else {
-            p.advance_with_error("expected value");
+            p.advance_with_error(VALUE);
         }
     }
 
@@ -360,8 +391,126 @@ fn value(p: &mut Parser) {
     } else if p.at(TokenKind::LBracket) {
         array(p);
     } else {
-        p.expect_some(VALUE, "value");
+        p.expect_some(VALUE);
     }
 
     p.pop_context();
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use indoc::indoc;
+
+    fn errors(input: &str) -> Vec<Error> {
+        Parser::parse(input).1
+    }
+
+    #[test]
+    fn valid() {
+        assert_eq!(errors(""), Vec::new());
+        assert_eq!(errors("{ a: 1, b: [2, 3] }"), Vec::new());
+        assert_eq!(errors("a: 1\nb: { c: true }\n"), Vec::new());
+        // Keys in nested maps aren't mistaken for values.
+        assert_eq!(errors("a: { 1: 2, true: null }"), Vec::new());
+    }
+
+    #[test]
+    fn nested_keys() {
+        // Kinds of the significant tokens the parser took.
+        let kinds = |input: &str| {
+            Parser::parse(input)
+                .0
+                .into_iter()
+                .filter_map(|event| match event {
+                    Event::Advance { token } if !SEPARATOR.contains(&token.kind) => {
+                        Some(token.kind)
+                    }
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // The first key of a map which is a value is lexed as a key, not as a
+        // number or keyword, and the map's values are still lexed as values.
+        assert_eq!(
+            kinds("a: { 1: 2 }"),
+            [
+                TokenKind::TextUnquoted,
+                TokenKind::Colon,
+                TokenKind::LBrace,
+                TokenKind::TextUnquoted,
+                TokenKind::Colon,
+                TokenKind::Integer,
+                TokenKind::RBrace,
+            ]
+        );
+        assert_eq!(
+            kinds("a: [{ true: 1\n}]"),
+            [
+                TokenKind::TextUnquoted,
+                TokenKind::Colon,
+                TokenKind::LBracket,
+                TokenKind::LBrace,
+                TokenKind::TextUnquoted,
+                TokenKind::Colon,
+                TokenKind::Integer,
+                TokenKind::RBrace,
+                TokenKind::RBracket,
+            ]
+        );
+    }
+
+    #[test]
+    fn syntax_errors() {
+        let input = indoc! {"
+            a: 1
+            b 2
+            c: [1,, 2]
+            }
+        "};
+
+        assert_eq!(
+            errors(input),
+            [
+                Error {
+                    range: 7..8,
+                    expected: Vec::from([TokenKind::Colon]),
+                    got: TokenKind::TextUnquoted,
+                },
+                Error {
+                    range: 15..16,
+                    expected: VALUE.to_vec(),
+                    got: TokenKind::Comma,
+                },
+                Error {
+                    range: 20..21,
+                    expected: KEY.to_vec(),
+                    got: TokenKind::RBrace,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn unclosed() {
+        let errors = errors("{ a: [1");
+
+        assert_eq!(
+            errors,
+            [
+                Error {
+                    range: 7..7,
+                    expected: Vec::from([TokenKind::RBracket]),
+                    got: TokenKind::Eof,
+                },
+                Error {
+                    range: 7..7,
+                    expected: Vec::from([TokenKind::RBrace]),
+                    got: TokenKind::Eof,
+                },
+            ]
+        );
+        assert_eq!(errors[0].to_string(), "expected ], got EOF");
+    }
+}
@@ -0,0 +1,1054 @@
+//! Serializing Rust data structures into Hjson with [`serde`].
+//!
+//! Data is written one member per line, with [`Options`] for each of the
+//! choices the linter can be configured to check, so output can be made to
+//! pass any configuration.
+//!
+//! ```
+//! use serde::Serialize;
+//!
+//! #[derive(Serialize)]
+//! struct Config {
+//!     name: String,
+//!     ports: Vec<u16>,
+//! }
+//!
+//! let config = Config {
+//!     name: String::from("my server"),
+//!     ports: Vec::from([80, 443]),
+//! };
+//! let hjson = hjson_parser::to_string(&config).unwrap();
+//! assert_eq!(hjson, "name: my server\nports: [\n  80\n  443\n]\n");
+//! ```
+
+use std::error;
+use std::fmt::{self, Display};
+use std::io;
+
+use serde::ser::{self, Impossible, Serialize};
+
+use crate::lexer::{self, Context};
+use crate::token::TokenKind;
+
+/// Serialize `value` as an Hjson document with the default [`Options`].
+///
+/// The root of a document is a map, so `value` must serialize as a map or a
+/// struct.
+pub fn to_string<T: Serialize + ?Sized>(value: &T) -> Result<String, Error> {
+    to_string_with(value, &Options::default())
+}
+
+/// Serialize `value` as an Hjson document with the given options.
+pub fn to_string_with<T: Serialize + ?Sized>(
+    value: &T,
+    options: &Options,
+) -> Result<String, Error> {
+    let node = value.serialize(NodeSerializer)?;
+
+    let mut writer = Writer {
+        options,
+        out: String::new(),
+    };
+    writer.document(&node)?;
+
+    Ok(writer.out)
+}
+
+/// Serialize `value` as an Hjson document into `writer` with the default
+/// [`Options`].
+pub fn to_writer<W: io::Write, T: Serialize + ?Sized>(writer: W, value: &T) -> Result<(), Error> {
+    to_writer_with(writer, value, &Options::default())
+}
+
+/// Serialize `value` as an Hjson document into `writer` with the given
+/// options.
+pub fn to_writer_with<W: io::Write, T: Serialize + ?Sized>(
+    mut writer: W,
+    value: &T,
+    options: &Options,
+) -> Result<(), Error> {
+    let output = to_string_with(value, options)?;
+    writer
+        .write_all(output.as_bytes())
+        .map_err(|err| Error::new(err.to_string()))
+}
+
+/// Choices for how to write documents.
+///
+/// The defaults produce idiomatic Hjson: no braces around the document,
+/// strings and keys without quotes where possible, and new lines instead of
+/// commas. See [`Options::json`] for the opposite.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Options {
+    /// Whether to surround the whole document with braces.
+    pub root_braces: bool,
+    /// Whether to leave keys unquoted where possible.
+    pub unquoted_keys: bool,
+    /// Whether to leave string values unquoted where possible. Values with a
+    /// comma after them are always quoted.
+    pub unquoted_values: bool,
+    /// Where to put commas between the members of maps and arrays.
+    pub commas: Commas,
+    /// Whether to write strings containing new lines as `'''` multi-line
+    /// strings where possible.
+    pub multi_line_strings: bool,
+    /// Number of spaces to indent by.
+    pub indent: usize,
+}
+
+/// Where to put commas between members, each of which is on its own line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Commas {
+    /// Separate members with new lines only.
+    Never,
+    /// After every member except the last.
+    Separate,
+    /// After every member, including the last.
+    Trailing,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            root_braces: false,
+            unquoted_keys: true,
+            unquoted_values: true,
+            commas: Commas::Never,
+            multi_line_strings: true,
+            indent: 2,
+        }
+    }
+}
+
+impl Options {
+    /// Options which only use JSON syntax.
+    pub fn json() -> Self {
+        Self {
+            root_braces: true,
+            unquoted_keys: false,
+            unquoted_values: false,
+            commas: Commas::Separate,
+            multi_line_strings: false,
+            indent: 2,
+        }
+    }
+}
+
+/// An error serializing a value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Error {
+    message: String,
+}
+
+impl Error {
+    fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl error::Error for Error {}
+
+impl ser::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self::new(msg.to_string())
+    }
+}
+
+/// A serialized value, collected before writing so that the layout of each
+/// member can depend on what follows it.
+#[derive(Clone, Debug, PartialEq)]
+enum Node {
+    Null,
+    Bool(bool),
+    /// A number, already formatted.
+    Number(String),
+    String(String),
+    Array(Vec<Node>),
+    Map(Vec<(String, Node)>),
+}
+
+/// Writer of the collected nodes.
+struct Writer<'o> {
+    options: &'o Options,
+    out: String,
+}
+
+impl Writer<'_> {
+    fn document(&mut self, root: &Node) -> Result<(), Error> {
+        let Node::Map(members) = root else {
+            return Err(Error::new("the root of a document must be a map"));
+        };
+
+        if self.options.root_braces {
+            self.map(members, 0);
+            self.out.push('\n');
+        } else {
+            self.members(members.iter().map(|(k, v)| (Some(k.as_str()), v)), 0);
+        }
+
+        Ok(())
+    }
+
+    fn map(&mut self, members: &[(String, Node)], depth: usize) {
+        if members.is_empty() {
+            self.out.push_str("{}");
+            return;
+        }
+
+        self.out.push_str("{\n");
+        self.members(
+            members.iter().map(|(k, v)| (Some(k.as_str()), v)),
+            depth + 1,
+        );
+        self.indent(depth);
+        self.out.push('}');
+    }
+
+    fn array(&mut self, values: &[Node], depth: usize) {
+        if values.is_empty() {
+            self.out.push_str("[]");
+            return;
+        }
+
+        self.out.push_str("[\n");
+        self.members(values.iter().map(|value| (None, value)), depth + 1);
+        self.indent(depth);
+        self.out.push(']');
+    }
+
+    /// Write the members of a map, or the values of an array, one per line.
+    fn members<'n>(
+        &mut self,
+        members: impl ExactSizeIterator<Item = (Option<&'n str>, &'n Node)>,
+        depth: usize,
+    ) {
+        let len = members.len();
+
+        for (i, (key, value)) in members.enumerate() {
+            let comma = match self.options.commas {
+                Commas::Never => false,
+                Commas::Separate => i + 1 < len,
+                Commas::Trailing => true,
+            };
+
+            self.indent(depth);
+            if let Some(key) = key {
+                self.key(key);
+                self.out.push(':');
+            }
+            self.value(value, key.is_some(), depth, comma);
+            if comma {
+                self.out.push(',');
+            }
+            self.out.push('\n');
+        }
+    }
+
+    fn key(&mut self, key: &str) {
+        match self.options.unquoted_keys && lexes_unquoted(key, Context::Key) {
+            true => self.out.push_str(key),
+            false => self.out.push_str(&quote(key)),
+        }
+    }
+
+    /// Write a value, after a key if `in_map`, and before a comma if `comma`.
+    fn value(&mut self, value: &Node, in_map: bool, depth: usize, comma: bool) {
+        if let Node::String(s) = value {
+            if self.options.multi_line_strings && can_be_multi_line(s) {
+                // The string goes on its own lines, indented past the key so
+                // that its indentation is kept.
+                let depth = depth + in_map as usize;
+                if in_map {
+                    self.out.push('\n');
+                    self.indent(depth);
+                }
+                self.multi_line(s, depth);
+                return;
+            }
+        }
+
+        if in_map {
+            self.out.push(' ');
+        }
+
+        match value {
+            Node::Null => self.out.push_str("null"),
+            Node::Bool(b) => self.out.push_str(if *b { "true" } else { "false" }),
+            Node::Number(n) => self.out.push_str(n),
+            Node::String(s) => {
+                // Unquoted strings run to the end of the line, so would
+                // include any comma after them.
+                match self.options.unquoted_values && !comma && lexes_unquoted(s, Context::Value) {
+                    true => self.out.push_str(s),
+                    false => self.out.push_str(&quote(s)),
+                }
+            }
+            Node::Array(values) => self.array(values, depth),
+            Node::Map(members) => self.map(members, depth),
+        }
+    }
+
+    /// Write a multi-line string whose opening quotes have just been indented
+    /// to `depth`.
+    fn multi_line(&mut self, s: &str, depth: usize) {
+        self.out.push_str("'''\n");
+        for line in s.split('\n') {
+            if !line.is_empty() {
+                self.indent(depth);
+                self.out.push_str(line);
+            }
+            self.out.push('\n');
+        }
+        self.indent(depth);
+        self.out.push_str("'''");
+    }
+
+    fn indent(&mut self, depth: usize) {
+        self.out
+            .extend(std::iter::repeat_n(' ', depth * self.options.indent));
+    }
+}
+
+/// Whether `text` would be lexed as a single unquoted string in the given
+/// context.
+///
+/// An unterminated block comment lexes as unquoted text, but would become a
+/// comment if a `*/` were written after it, so text starting with `/*` is
+/// always quoted like text starting with `//` or `#`.
+fn lexes_unquoted(text: &str, context: Context) -> bool {
+    let token = lexer::token(text, &context);
+    !text.is_empty()
+        && !text.starts_with("/*")
+        && token.kind == TokenKind::TextUnquoted
+        && token.len == text.len()
+}
+
+/// Whether a string can be written as a multi-line string and decoded back
+/// to the same text, without trailing whitespace on any line.
+fn can_be_multi_line(s: &str) -> bool {
+    s.contains('\n')
+        && !s.contains("'''")
+        && !s.contains('\r')
+        && s.split('\n').all(|line| line.trim_end() == line)
+}
+
+/// Quote text as a double-quoted string, escaping it as necessary.
+fn quote(text: &str) -> String {
+    let mut quoted = String::from("\"");
+
+    for c in text.chars() {
+        match c {
+            '"' => quoted += "\\\"",
+            '\\' => quoted += "\\\\",
+            '\t' => quoted += "\\t",
+            '\r' => quoted += "\\r",
+            '\n' => quoted += "\\n",
+            c if c.is_control() => quoted += &format!("\\u{:04x}", c as u32),
+            c => quoted.push(c),
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
+
+/// Serializer collecting values into [`Node`]s.
+struct NodeSerializer;
+
+impl ser::Serializer for NodeSerializer {
+    type Ok = Node;
+    type Error = Error;
+
+    type SerializeSeq = SeqSerializer;
+    type SerializeTuple = SeqSerializer;
+    type SerializeTupleStruct = SeqSerializer;
+    type SerializeTupleVariant = VariantSerializer<SeqSerializer>;
+    type SerializeMap = MapSerializer;
+    type SerializeStruct = MapSerializer;
+    type SerializeStructVariant = VariantSerializer<MapSerializer>;
+
+    fn serialize_bool(self, v: bool) -> Result<Node, Error> {
+        Ok(Node::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Node, Error> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Node, Error> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Node, Error> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Node, Error> {
+        Ok(Node::Number(v.to_string()))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Node, Error> {
+        Ok(Node::Number(v.to_string()))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Node, Error> {
+        self.serialize_u64(v.into())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Node, Error> {
+        self.serialize_u64(v.into())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Node, Error> {
+        self.serialize_u64(v.into())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Node, Error> {
+        Ok(Node::Number(v.to_string()))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Node, Error> {
+        Ok(Node::Number(v.to_string()))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Node, Error> {
+        self.serialize_f64(v.into())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Node, Error> {
+        match v.is_finite() {
+            // Debug formatting keeps the fraction of whole numbers, so they're
+            // read back as floats.
+            true => Ok(Node::Number(format!("{v:?}"))),
+            false => Err(Error::new(format!("{v} cannot be written in Hjson"))),
+        }
+    }
+
+    fn serialize_char(self, v: char) -> Result<Node, Error> {
+        Ok(Node::String(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Node, Error> {
+        Ok(Node::String(v.to_string()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Node, Error> {
+        Ok(Node::Array(
+            v.iter().map(|b| Node::Number(b.to_string())).collect(),
+        ))
+    }
+
+    fn serialize_none(self) -> Result<Node, Error> {
+        Ok(Node::Null)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Node, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Node, Error> {
+        Ok(Node::Null)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Node, Error> {
+        Ok(Node::Null)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<Node, Error> {
+        Ok(Node::String(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Node, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Node, Error> {
+        let value = value.serialize(self)?;
+        Ok(Node::Map(Vec::from([(variant.to_string(), value)])))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SeqSerializer, Error> {
+        Ok(SeqSerializer(Vec::with_capacity(len.unwrap_or(0))))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SeqSerializer, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SeqSerializer, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<VariantSerializer<SeqSerializer>, Error> {
+        Ok(VariantSerializer {
+            variant,
+            inner: self.serialize_seq(Some(len))?,
+        })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<MapSerializer, Error> {
+        Ok(MapSerializer {
+            members: Vec::with_capacity(len.unwrap_or(0)),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<MapSerializer, Error> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<VariantSerializer<MapSerializer>, Error> {
+        Ok(VariantSerializer {
+            variant,
+            inner: self.serialize_map(Some(len))?,
+        })
+    }
+}
+
+struct SeqSerializer(Vec<Node>);
+
+impl ser::SerializeSeq for SeqSerializer {
+    type Ok = Node;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.0.push(value.serialize(NodeSerializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Node, Error> {
+        Ok(Node::Array(self.0))
+    }
+}
+
+impl ser::SerializeTuple for SeqSerializer {
+    type Ok = Node;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Node, Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for SeqSerializer {
+    type Ok = Node;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Node, Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+struct MapSerializer {
+    members: Vec<(String, Node)>,
+    /// The key of the member whose value is next.
+    key: Option<String>,
+}
+
+impl ser::SerializeMap for MapSerializer {
+    type Ok = Node;
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        self.key = Some(key.serialize(KeySerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self.key.take().expect("value serialized before key");
+        self.members.push((key, value.serialize(NodeSerializer)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Node, Error> {
+        Ok(Node::Map(self.members))
+    }
+}
+
+impl ser::SerializeStruct for MapSerializer {
+    type Ok = Node;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.members
+            .push((key.to_string(), value.serialize(NodeSerializer)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Node, Error> {
+        Ok(Node::Map(self.members))
+    }
+}
+
+/// Serializer for tuple and struct variants, which are written as maps with
+/// the variant as their only key.
+struct VariantSerializer<S> {
+    variant: &'static str,
+    inner: S,
+}
+
+impl ser::SerializeTupleVariant for VariantSerializer<SeqSerializer> {
+    type Ok = Node;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        ser::SerializeSeq::serialize_element(&mut self.inner, value)
+    }
+
+    fn end(self) -> Result<Node, Error> {
+        let value = ser::SerializeSeq::end(self.inner)?;
+        Ok(Node::Map(Vec::from([(self.variant.to_string(), value)])))
+    }
+}
+
+impl ser::SerializeStructVariant for VariantSerializer<MapSerializer> {
+    type Ok = Node;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        ser::SerializeStruct::serialize_field(&mut self.inner, key, value)
+    }
+
+    fn end(self) -> Result<Node, Error> {
+        let value = ser::SerializeStruct::end(self.inner)?;
+        Ok(Node::Map(Vec::from([(self.variant.to_string(), value)])))
+    }
+}
+
+/// Serializer for map keys, which must be strings, or scalars which can be
+/// written as strings.
+struct KeySerializer;
+
+impl KeySerializer {
+    fn invalid<T>(self) -> Result<T, Error> {
+        Err(Error::new("map keys must be strings"))
+    }
+}
+
+impl ser::Serializer for KeySerializer {
+    type Ok = String;
+    type Error = Error;
+
+    type SerializeSeq = Impossible<String, Error>;
+    type SerializeTuple = Impossible<String, Error>;
+    type SerializeTupleStruct = Impossible<String, Error>;
+    type SerializeTupleVariant = Impossible<String, Error>;
+    type SerializeMap = Impossible<String, Error>;
+    type SerializeStruct = Impossible<String, Error>;
+    type SerializeStructVariant = Impossible<String, Error>;
+
+    fn serialize_bool(self, v: bool) -> Result<String, Error> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<String, Error> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<String, Error> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<String, Error> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<String, Error> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<String, Error> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<String, Error> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<String, Error> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<String, Error> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<String, Error> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<String, Error> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<String, Error> {
+        self.invalid()
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<String, Error> {
+        self.invalid()
+    }
+
+    fn serialize_char(self, v: char) -> Result<String, Error> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<String, Error> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<String, Error> {
+        self.invalid()
+    }
+
+    fn serialize_none(self) -> Result<String, Error> {
+        self.invalid()
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<String, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<String, Error> {
+        self.invalid()
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<String, Error> {
+        self.invalid()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<String, Error> {
+        Ok(variant.to_string())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<String, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<String, Error> {
+        self.invalid()
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        self.invalid()
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Error> {
+        self.invalid()
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Error> {
+        self.invalid()
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        self.invalid()
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        self.invalid()
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Error> {
+        self.invalid()
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        self.invalid()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::from_str;
+    use indoc::indoc;
+    use serde::{Deserialize, Serialize};
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct Config {
+        name: String,
+        description: String,
+        port: u16,
+        ratio: f64,
+        debug: Option<bool>,
+        tags: Vec<String>,
+        mode: Mode,
+        limits: BTreeMap<u32, i64>,
+        empty: Vec<u8>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    #[serde(rename_all = "kebab-case")]
+    enum Mode {
+        Fast,
+        Slow { delay: u32 },
+    }
+
+    fn config() -> Config {
+        Config {
+            name: String::from("my server"),
+            description: String::from("first line\n  indented\n\nlast"),
+            port: 8080,
+            ratio: 1.0,
+            debug: None,
+            tags: Vec::from([String::from("a, b"), String::from("true")]),
+            mode: Mode::Slow { delay: 10 },
+            limits: BTreeMap::from([(1, -1), (2, 100)]),
+            empty: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn default_options() {
+        let output = to_string(&config()).unwrap();
+
+        assert_eq!(
+            output,
+            indoc! {r#"
+                name: my server
+                description:
+                  '''
+                  first line
+                    indented
+
+                  last
+                  '''
+                port: 8080
+                ratio: 1.0
+                debug: null
+                tags: [
+                  a, b
+                  "true"
+                ]
+                mode: {
+                  slow: {
+                    delay: 10
+                  }
+                }
+                limits: {
+                  1: -1
+                  2: 100
+                }
+                empty: []
+            "#}
+        );
+        assert_eq!(from_str::<Config>(&output).unwrap(), config());
+    }
+
+    #[test]
+    fn json_options() {
+        let output = to_string_with(&config(), &Options::json()).unwrap();
+
+        assert_eq!(
+            output,
+            indoc! {r#"
+                {
+                  "name": "my server",
+                  "description": "first line\n  indented\n\nlast",
+                  "port": 8080,
+                  "ratio": 1.0,
+                  "debug": null,
+                  "tags": [
+                    "a, b",
+                    "true"
+                  ],
+                  "mode": {
+                    "slow": {
+                      "delay": 10
+                    }
+                  },
+                  "limits": {
+                    "1": -1,
+                    "2": 100
+                  },
+                  "empty": []
+                }
+            "#}
+        );
+        assert_eq!(from_str::<Config>(&output).unwrap(), config());
+    }
+
+    #[test]
+    fn trailing_commas() {
+        let options = Options {
+            commas: Commas::Trailing,
+            indent: 4,
+            ..Options::default()
+        };
+        let value = BTreeMap::from([("a", Vec::from(["x", "y z"])), ("b c", Vec::new())]);
+        let output = to_string_with(&value, &options).unwrap();
+
+        assert_eq!(
+            output,
+            indoc! {r#"
+                a: [
+                    "x",
+                    "y z",
+                ],
+                "b c": [],
+            "#}
+        );
+        assert_eq!(
+            from_str::<BTreeMap<String, Vec<String>>>(&output)
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn strings() {
+        let cases = [
+            "",
+            " leading",
+            "trailing ",
+            "# comment",
+            "'quoted'",
+            "12",
+            "null",
+            "{",
+            "multi\nline ",
+            "multi\n'''\nline",
+            "\ttab\u{1}",
+        ];
+
+        for case in cases {
+            let output = to_string(&BTreeMap::from([("key", case)])).unwrap();
+            let decoded: BTreeMap<String, String> = from_str(&output).unwrap();
+            assert_eq!(decoded["key"], case, "{output}");
+        }
+    }
+
+    #[test]
+    fn comment_markers() {
+        // Keys and values starting with comment markers, followed by text
+        // which would end a block comment.
+        let markers = ["#", "//", "/*", "/*a", "/**/", "*/", "/"];
+
+        for marker in markers {
+            let value = BTreeMap::from([
+                (marker.to_string(), format!("{marker}x")),
+                (String::from("a"), String::from("*/")),
+                (format!("{marker}\""), String::from("[")),
+            ]);
+
+            for options in [Options::default(), Options::json()] {
+                let output = to_string_with(&value, &options).unwrap();
+                let decoded: BTreeMap<String, String> = from_str(&output).unwrap();
+                assert_eq!(decoded, value, "{output}");
+            }
+        }
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            to_string(&[1, 2]).unwrap_err().to_string(),
+            "the root of a document must be a map"
+        );
+        assert_eq!(
+            to_string(&BTreeMap::from([((), 1)]))
+                .unwrap_err()
+                .to_string(),
+            "map keys must be strings"
+        );
+        assert_eq!(
+            to_string(&BTreeMap::from([("a", f64::NAN)]))
+                .unwrap_err()
+                .to_string(),
+            "NaN cannot be written in Hjson"
+        );
+    }
+}
@@ -0,0 +1,217 @@
+//! Values deserialized along with where they came from.
+
+use std::fmt;
+use std::ops::{Deref, DerefMut, Range};
+
+use serde::de::{self, Deserialize, Deserializer, MapAccess, Visitor};
+use serde::ser::{Serialize, Serializer};
+
+use crate::cursor::Cursor;
+
+// The Hjson deserializer recognises this struct name and fills in the fields
+// below, which no other deserializer will.
+pub(crate) const NAME: &str = "$__hjson_parser_private_Spanned";
+pub(crate) const START: &str = "$__hjson_parser_private_start";
+pub(crate) const END: &str = "$__hjson_parser_private_end";
+pub(crate) const LINE: &str = "$__hjson_parser_private_line";
+pub(crate) const COLUMN: &str = "$__hjson_parser_private_column";
+pub(crate) const VALUE: &str = "$__hjson_parser_private_value";
+pub(crate) const FIELDS: &[&str] = &[START, END, LINE, COLUMN, VALUE];
+
+/// A deserialized value along with its position in the source.
+///
+/// This can only be deserialized by the Hjson [deserializer](crate::de), and
+/// lets values be checked after deserialization while still pointing back
+/// at the source:
+///
+/// ```
+/// use hjson_parser::Spanned;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Config {
+///     port: Spanned<u16>,
+/// }
+///
+/// let config: Config = hjson_parser::from_str("port: 80").unwrap();
+/// if *config.port < 1024 {
+///     let cursor = config.port.cursor();
+///     assert_eq!((cursor.line, cursor.column), (1, 7));
+/// }
+/// ```
+///
+/// Comparisons and hashing only consider the value, not where it came from.
+#[derive(Clone, Debug)]
+pub struct Spanned<T> {
+    span: Range<usize>,
+    cursor: Cursor,
+    value: T,
+}
+
+impl<T> Spanned<T> {
+    /// A value with the given position in the source.
+    pub fn new(span: Range<usize>, cursor: Cursor, value: T) -> Self {
+        Self {
+            span,
+            cursor,
+            value,
+        }
+    }
+
+    /// Byte range of the value in the source.
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    /// Position of the start of the value in the source.
+    pub fn cursor(&self) -> Cursor {
+        self.cursor
+    }
+
+    /// Reference to the value.
+    pub fn get_ref(&self) -> &T {
+        &self.value
+    }
+
+    /// Mutable reference to the value.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+
+    /// The value, without its position.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T> Deref for Spanned<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for Spanned<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+impl<T: PartialEq> PartialEq for Spanned<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T: Eq> Eq for Spanned<T> {}
+
+impl<T: std::hash::Hash> std::hash::Hash for Spanned<T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.value.hash(state);
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Spanned<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_struct(NAME, FIELDS, SpannedVisitor(std::marker::PhantomData))
+    }
+}
+
+impl<T: Serialize> Serialize for Spanned<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.value.serialize(serializer)
+    }
+}
+
+struct SpannedVisitor<T>(std::marker::PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for SpannedVisitor<T> {
+    type Value = Spanned<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a value from the Hjson deserializer")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Spanned<T>, A::Error> {
+        let mut field = |name: &'static str| -> Result<usize, A::Error> {
+            match map.next_key::<String>()? {
+                Some(key) if key == name => map.next_value(),
+                _ => Err(de::Error::custom(
+                    "Spanned can only be deserialized from Hjson",
+                )),
+            }
+        };
+
+        let start = field(START)?;
+        let end = field(END)?;
+        let line = field(LINE)?;
+        let column = field(COLUMN)?;
+
+        match map.next_key::<String>()? {
+            Some(key) if key == VALUE => Ok(Spanned {
+                span: start..end,
+                cursor: Cursor::new(line, column, start),
+                value: map.next_value()?,
+            }),
+            _ => Err(de::Error::custom(
+                "Spanned can only be deserialized from Hjson",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::from_str;
+    use indoc::indoc;
+    use serde::de::value::{self, MapDeserializer};
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, serde::Deserialize)]
+    struct Config {
+        name: Spanned<String>,
+        ports: Vec<Spanned<u16>>,
+        limits: Spanned<BTreeMap<String, Spanned<Option<u32>>>>,
+    }
+
+    #[test]
+    fn spans() {
+        let config: Config = from_str(indoc! {"
+            name: server
+            ports: [80, 8080]
+            limits: {
+              max: null
+            }
+        "})
+        .unwrap();
+
+        assert_eq!(*config.name, "server");
+        assert_eq!(config.name.span(), 6..12);
+        assert_eq!(config.name.cursor(), Cursor::new(1, 7, 6));
+
+        let ports: Vec<_> = config.ports.iter().map(|port| port.cursor()).collect();
+        assert_eq!(ports, [Cursor::new(2, 9, 21), Cursor::new(2, 13, 25)]);
+
+        assert_eq!(config.limits.span(), 39..54);
+        let max = &config.limits["max"];
+        assert_eq!(max.get_ref(), &None);
+        assert_eq!(max.cursor(), Cursor::new(4, 8, 48));
+    }
+
+    #[test]
+    fn errors() {
+        // Errors within a spanned value still point at it.
+        let err = from_str::<Config>("name: server\nports: [80, -1]\nlimits: {}").unwrap_err();
+        assert_eq!(err.cursor(), Some(Cursor::new(2, 13, 25)));
+
+        // Other deserializers can't fill in spans.
+        let map = MapDeserializer::<_, value::Error>::new([("value", 1u32)].into_iter());
+        let err = Spanned::<u32>::deserialize(map).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Spanned can only be deserialized from Hjson"
+        );
+    }
+}
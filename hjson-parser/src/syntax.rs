@@ -0,0 +1,341 @@
+//! Navigable views of a parsed [`Tree`].
+//!
+//! A [`Tree`] only knows the kinds and lengths of what it contains. The views
+//! here pair it with the source text, so each node and token knows its byte
+//! range and text, and can find its parent and siblings. Views are cheap to
+//! clone, and only borrow the tree and source.
+
+use std::fmt;
+use std::ops::Range;
+use std::rc::Rc;
+
+use crate::token::{Token, TokenKind};
+use crate::tree::{Child, Tree, TreeKind};
+
+/// A view of a [`Tree`] at some position in the source.
+#[derive(Clone)]
+pub struct SyntaxNode<'a> {
+    data: Rc<NodeData<'a>>,
+}
+
+struct NodeData<'a> {
+    source: &'a str,
+    tree: &'a Tree,
+    offset: usize,
+    /// The parent node and this node's index in its children.
+    parent: Option<(SyntaxNode<'a>, usize)>,
+}
+
+/// A view of a [`Token`] at some position in the source.
+#[derive(Clone)]
+pub struct SyntaxToken<'a> {
+    parent: SyntaxNode<'a>,
+    token: Token,
+    offset: usize,
+    index: usize,
+}
+
+/// Either a node or a token, as found among the children of a node.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SyntaxElement<'a> {
+    Node(SyntaxNode<'a>),
+    Token(SyntaxToken<'a>),
+}
+
+impl<'a> SyntaxNode<'a> {
+    /// A view of the whole `tree`, which must have been parsed from `source`.
+    pub fn new_root(source: &'a str, tree: &'a Tree) -> Self {
+        assert_eq!(tree.len(), source.len(), "tree was not parsed from source");
+
+        Self {
+            data: Rc::new(NodeData {
+                source,
+                tree,
+                offset: 0,
+                parent: None,
+            }),
+        }
+    }
+
+    /// The kind of tree this node views.
+    pub fn kind(&self) -> TreeKind {
+        self.data.tree.kind()
+    }
+
+    /// The tree this node views.
+    pub fn tree(&self) -> &'a Tree {
+        self.data.tree
+    }
+
+    /// Byte range of this node in the source.
+    pub fn range(&self) -> Range<usize> {
+        self.data.offset..self.data.offset + self.data.tree.len()
+    }
+
+    /// Source text of this node, including any comments and whitespace.
+    pub fn text(&self) -> &'a str {
+        &self.data.source[self.range()]
+    }
+
+    /// The node containing this one, unless this is the root.
+    pub fn parent(&self) -> Option<SyntaxNode<'a>> {
+        self.data.parent.as_ref().map(|(parent, _)| parent.clone())
+    }
+
+    /// This node and each node containing it, up to the root.
+    pub fn ancestors(&self) -> impl Iterator<Item = SyntaxNode<'a>> {
+        std::iter::successors(Some(self.clone()), SyntaxNode::parent)
+    }
+
+    /// The nodes directly within this one, skipping tokens.
+    pub fn children(&self) -> impl Iterator<Item = SyntaxNode<'a>> {
+        self.children_with_tokens()
+            .filter_map(SyntaxElement::into_node)
+    }
+
+    /// The nodes and tokens directly within this one, in order.
+    pub fn children_with_tokens(&self) -> impl Iterator<Item = SyntaxElement<'a>> {
+        let parent = self.clone();
+        let mut offset = self.data.offset;
+
+        self.data
+            .tree
+            .children()
+            .iter()
+            .enumerate()
+            .map(move |(index, child)| {
+                let start = offset;
+                offset += child.len();
+                parent.child(child, start, index)
+            })
+    }
+
+    /// The first token within this node or any of its descendants.
+    pub fn first_token(&self) -> Option<SyntaxToken<'a>> {
+        self.children_with_tokens().find_map(|child| match child {
+            SyntaxElement::Node(node) => node.first_token(),
+            SyntaxElement::Token(token) => Some(token),
+        })
+    }
+
+    /// The next node with the same parent, skipping tokens.
+    pub fn next_sibling(&self) -> Option<SyntaxNode<'a>> {
+        let (parent, index) = self.data.parent.as_ref()?;
+        next(parent, *index).find_map(SyntaxElement::into_node)
+    }
+
+    /// The previous node with the same parent, skipping tokens.
+    pub fn prev_sibling(&self) -> Option<SyntaxNode<'a>> {
+        let (parent, index) = self.data.parent.as_ref()?;
+        prev(parent, *index).find_map(SyntaxElement::into_node)
+    }
+
+    /// The next node or token with the same parent.
+    pub fn next_sibling_or_token(&self) -> Option<SyntaxElement<'a>> {
+        let (parent, index) = self.data.parent.as_ref()?;
+        next(parent, *index).next()
+    }
+
+    /// The previous node or token with the same parent.
+    pub fn prev_sibling_or_token(&self) -> Option<SyntaxElement<'a>> {
+        let (parent, index) = self.data.parent.as_ref()?;
+        prev(parent, *index).next()
+    }
+
+    /// A view of one of this node's children.
+    fn child(&self, child: &'a Child, offset: usize, index: usize) -> SyntaxElement<'a> {
+        match child {
+            Child::Tree(tree) => SyntaxElement::Node(SyntaxNode {
+                data: Rc::new(NodeData {
+                    source: self.data.source,
+                    tree,
+                    offset,
+                    parent: Some((self.clone(), index)),
+                }),
+            }),
+            Child::Token(token) => SyntaxElement::Token(SyntaxToken {
+                parent: self.clone(),
+                token: *token,
+                offset,
+                index,
+            }),
+        }
+    }
+}
+
+impl<'a> SyntaxToken<'a> {
+    /// The kind of this token.
+    pub fn kind(&self) -> TokenKind {
+        self.token.kind
+    }
+
+    /// Byte range of this token in the source.
+    pub fn range(&self) -> Range<usize> {
+        self.offset..self.offset + self.token.len
+    }
+
+    /// Source text of this token.
+    pub fn text(&self) -> &'a str {
+        &self.parent.data.source[self.range()]
+    }
+
+    /// The whole source this token is in.
+    pub(crate) fn source(&self) -> &'a str {
+        self.parent.data.source
+    }
+
+    /// The node containing this token.
+    pub fn parent(&self) -> SyntaxNode<'a> {
+        self.parent.clone()
+    }
+
+    /// The next node or token with the same parent.
+    pub fn next_sibling_or_token(&self) -> Option<SyntaxElement<'a>> {
+        next(&self.parent, self.index).next()
+    }
+
+    /// The previous node or token with the same parent.
+    pub fn prev_sibling_or_token(&self) -> Option<SyntaxElement<'a>> {
+        prev(&self.parent, self.index).next()
+    }
+}
+
+impl<'a> SyntaxElement<'a> {
+    /// Byte range of this element in the source.
+    pub fn range(&self) -> Range<usize> {
+        match self {
+            Self::Node(node) => node.range(),
+            Self::Token(token) => token.range(),
+        }
+    }
+
+    /// Source text of this element.
+    pub fn text(&self) -> &'a str {
+        match self {
+            Self::Node(node) => node.text(),
+            Self::Token(token) => token.text(),
+        }
+    }
+
+    /// The node containing this element, unless this is the root.
+    pub fn parent(&self) -> Option<SyntaxNode<'a>> {
+        match self {
+            Self::Node(node) => node.parent(),
+            Self::Token(token) => Some(token.parent()),
+        }
+    }
+
+    /// This element as a node, if it is one.
+    pub fn into_node(self) -> Option<SyntaxNode<'a>> {
+        match self {
+            Self::Node(node) => Some(node),
+            Self::Token(_) => None,
+        }
+    }
+
+    /// This element as a token, if it is one.
+    pub fn into_token(self) -> Option<SyntaxToken<'a>> {
+        match self {
+            Self::Node(_) => None,
+            Self::Token(token) => Some(token),
+        }
+    }
+}
+
+/// Children of `parent` after the one at `index`, in order.
+fn next<'a>(parent: &SyntaxNode<'a>, index: usize) -> impl Iterator<Item = SyntaxElement<'a>> {
+    parent.children_with_tokens().skip(index + 1)
+}
+
+/// Children of `parent` before the one at `index`, nearest first.
+fn prev<'a>(parent: &SyntaxNode<'a>, index: usize) -> impl Iterator<Item = SyntaxElement<'a>> {
+    let before: Vec<_> = parent.children_with_tokens().take(index).collect();
+    before.into_iter().rev()
+}
+
+// Views are equal if they view the same part of the same tree.
+
+impl PartialEq for SyntaxNode<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self.data.tree, other.data.tree) && self.data.offset == other.data.offset
+    }
+}
+
+impl Eq for SyntaxNode<'_> {}
+
+impl PartialEq for SyntaxToken<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.parent == other.parent && self.index == other.index
+    }
+}
+
+impl Eq for SyntaxToken<'_> {}
+
+impl fmt::Debug for SyntaxNode<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}@{:?}", self.kind(), self.range())
+    }
+}
+
+impl fmt::Debug for SyntaxToken<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}@{:?} {:?}", self.kind(), self.range(), self.text())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parse;
+
+    #[test]
+    fn navigation() {
+        let source = "{ a: [1, 2] }";
+        let (tree, _) = parse(source);
+        let root = SyntaxNode::new_root(source, &tree);
+
+        let map = root.children().next().unwrap();
+        assert_eq!(map.kind(), TreeKind::Map);
+        assert_eq!(map.range(), 0..13);
+        assert_eq!(map.parent(), Some(root.clone()));
+
+        let mapping = map.children().next().unwrap();
+        let array = mapping.children().next().unwrap();
+        assert_eq!(array.kind(), TreeKind::Array);
+        assert_eq!(array.text(), "[1, 2]");
+        assert_eq!(array.ancestors().count(), 4);
+
+        let one = array
+            .first_token()
+            .unwrap()
+            .next_sibling_or_token()
+            .unwrap();
+        assert_eq!(one.text(), "1");
+        assert_eq!(one.range(), 6..7);
+
+        let comma = one.into_token().unwrap().next_sibling_or_token().unwrap();
+        assert_eq!(comma.text(), ",");
+        assert_eq!(
+            comma
+                .into_token()
+                .unwrap()
+                .prev_sibling_or_token()
+                .unwrap()
+                .text(),
+            "1"
+        );
+
+        // The array is the only node in its mapping.
+        assert_eq!(array.next_sibling(), None);
+        assert_eq!(array.prev_sibling(), None);
+        assert_eq!(
+            array
+                .prev_sibling_or_token()
+                .unwrap()
+                .into_token()
+                .unwrap()
+                .kind(),
+            TokenKind::Whitespace
+        );
+    }
+}
@@ -29,6 +29,9 @@ pub enum TokenKind {
     TextUnquoted,
     NewLine,
     Whitespace,
+    /// Text which could not be lexed, such as an unterminated string, up to
+    /// the end of the line.
+    Error,
 }
 
 impl TokenKind {
@@ -61,6 +64,7 @@ impl Display for TokenKind {
             Self::TextUnquoted => "unquoted string",
             Self::NewLine => "newline",
             Self::Whitespace => "whitespace",
+            Self::Error => "invalid token",
         };
         f.write_str(name)
     }
@@ -21,6 +21,8 @@ pub enum TreeKind {
 pub struct Tree {
     kind: TreeKind,
     children: Vec<Child>,
+    /// Length of the source text covered by this tree, in bytes.
+    len: usize,
 }
 
 /// A child of a tree in the parsed structure, which may be a single token or
@@ -31,7 +33,42 @@ pub enum Child {
     Tree(Tree),
 }
 
+impl Child {
+    /// Length of the source text covered by this child, in bytes.
+    pub fn len(&self) -> usize {
+        match self {
+            Child::Token(token) => token.len,
+            Child::Tree(tree) => tree.len,
+        }
+    }
+
+    /// Whether this child covers no source text at all.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
 impl Tree {
+    /// The kind of this tree.
+    pub fn kind(&self) -> TreeKind {
+        self.kind
+    }
+
+    /// The tokens and trees making up this tree, in order.
+    pub fn children(&self) -> &[Child] {
+        &self.children
+    }
+
+    /// Length of the source text covered by this tree, in bytes.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether this tree covers no source text at all.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
     /// Construct a tree from some stream of [`Event`]s. See [`parser::Parser`]
     /// for generating an event stream.
     pub(crate) fn build(mut events: Vec<Event>) -> Self {
@@ -48,15 +85,20 @@ impl Tree {
                 Event::Open { kind } => stack.push(Tree {
                     kind,
                     children: Vec::new(),
+                    len: 0,
                 }),
 
                 Event::Close => {
                     let tree = stack.pop().unwrap();
-                    stack.last_mut().unwrap().children.push(Child::Tree(tree));
+                    let parent = stack.last_mut().unwrap();
+                    parent.len += tree.len;
+                    parent.children.push(Child::Tree(tree));
                 }
 
                 Event::Advance { token } => {
-                    stack.last_mut().unwrap().children.push(Child::Token(token));
+                    let parent = stack.last_mut().unwrap();
+                    parent.len += token.len;
+                    parent.children.push(Child::Token(token));
                 }
             }
         }
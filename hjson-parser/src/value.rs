@@ -0,0 +1,629 @@
+//! Decoding of parsed documents into owned values.
+//!
+//! Every [`Value`] keeps the byte range it was decoded from, so that anything
+//! using the data can still point back at the source.
+
+use std::error;
+use std::fmt::{self, Display};
+use std::ops::Range;
+
+use serde::ser::{Serialize, SerializeMap, Serializer};
+
+use crate::ast::{self, AstNode};
+use crate::error::Error;
+use crate::syntax::SyntaxToken;
+use crate::token::TokenKind;
+
+/// A value decoded from an Hjson document, along with where it came from.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Value {
+    pub kind: ValueKind,
+    /// Byte range of the value in the source.
+    pub span: Range<usize>,
+}
+
+/// The different kinds of value.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ValueKind {
+    Null,
+    Bool(bool),
+    Number(Number),
+    String(String),
+    Array(Vec<Value>),
+    Map(Map),
+}
+
+/// A number, kept as an integer where possible.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Number {
+    /// An integer which is not negative.
+    U64(u64),
+    /// An integer which is negative.
+    I64(i64),
+    /// A number with a fraction or exponent, or an integer too large for the
+    /// other representations.
+    F64(f64),
+}
+
+/// A map of keys to values, in the order they were written.
+///
+/// Duplicate keys are kept, and lookups find the last of them.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Map {
+    pub members: Vec<Member>,
+}
+
+/// A single `key: value` member of a map.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Member {
+    pub key: Key,
+    pub value: Value,
+}
+
+/// A decoded key, along with where it came from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Key {
+    pub name: String,
+    /// Byte range of the key in the source, including any quotes.
+    pub span: Range<usize>,
+}
+
+/// An error decoding a document into a [`Value`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The document has syntax errors, so could not be decoded.
+    Syntax(Vec<Error>),
+    /// A quoted string has an invalid escape sequence at this byte range.
+    InvalidEscape(Range<usize>),
+}
+
+impl Value {
+    /// Decode the map at the root of a file.
+    ///
+    /// Anything missing because of syntax errors is left out, so files should
+    /// be checked for errors first. An empty file is an empty map.
+    pub fn from_file(file: &ast::File) -> Result<Self, DecodeError> {
+        match file.map() {
+            Some(root) => map(&root),
+            None => Ok(Value {
+                kind: ValueKind::Map(Map::default()),
+                span: file.range(),
+            }),
+        }
+    }
+
+    /// Whether this value is `null`.
+    pub fn is_null(&self) -> bool {
+        self.kind == ValueKind::Null
+    }
+
+    /// This value as a Boolean, if it is one.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self.kind {
+            ValueKind::Bool(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    /// This value as a number, if it is one.
+    pub fn as_number(&self) -> Option<Number> {
+        match self.kind {
+            ValueKind::Number(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    /// This value as a string, if it is one.
+    pub fn as_str(&self) -> Option<&str> {
+        match &self.kind {
+            ValueKind::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// This value as an array, if it is one.
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match &self.kind {
+            ValueKind::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// This value as a map, if it is one.
+    pub fn as_map(&self) -> Option<&Map> {
+        match &self.kind {
+            ValueKind::Map(map) => Some(map),
+            _ => None,
+        }
+    }
+}
+
+impl Number {
+    /// This number as a `u64`, if it is an integer that fits.
+    pub fn as_u64(self) -> Option<u64> {
+        match self {
+            Self::U64(n) => Some(n),
+            Self::I64(_) | Self::F64(_) => None,
+        }
+    }
+
+    /// This number as an `i64`, if it is an integer that fits.
+    pub fn as_i64(self) -> Option<i64> {
+        match self {
+            Self::U64(n) => i64::try_from(n).ok(),
+            Self::I64(n) => Some(n),
+            Self::F64(_) => None,
+        }
+    }
+
+    /// This number as an `f64`, which may lose precision for integers.
+    pub fn as_f64(self) -> f64 {
+        match self {
+            Self::U64(n) => n as f64,
+            Self::I64(n) => n as f64,
+            Self::F64(n) => n,
+        }
+    }
+}
+
+impl Map {
+    /// The value of the last member with this key.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.members
+            .iter()
+            .rev()
+            .find(|member| member.key.name == key)
+            .map(|member| &member.value)
+    }
+
+    /// The members of this map, in order.
+    pub fn iter(&self) -> impl Iterator<Item = &Member> {
+        self.members.iter()
+    }
+
+    /// The number of members, including any with duplicate keys.
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    /// Whether this map has no members.
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+}
+
+// Values serialize as the data they hold, so can be written as other formats.
+
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match &self.kind {
+            ValueKind::Null => serializer.serialize_unit(),
+            ValueKind::Bool(b) => serializer.serialize_bool(*b),
+            ValueKind::Number(n) => n.serialize(serializer),
+            ValueKind::String(s) => serializer.serialize_str(s),
+            ValueKind::Array(values) => values.serialize(serializer),
+            ValueKind::Map(map) => map.serialize(serializer),
+        }
+    }
+}
+
+impl Serialize for Number {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match *self {
+            Self::U64(n) => serializer.serialize_u64(n),
+            Self::I64(n) => serializer.serialize_i64(n),
+            Self::F64(n) => serializer.serialize_f64(n),
+        }
+    }
+}
+
+impl Serialize for Map {
+    /// Members are serialized in order, including any with duplicate keys.
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.len()))?;
+        for member in &self.members {
+            map.serialize_entry(&member.key.name, &member.value)?;
+        }
+        map.end()
+    }
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax(errors) => match errors.as_slice() {
+                [error] => write!(f, "{error}"),
+                [error, _] => write!(f, "{error} (and 1 more error)"),
+                [error, rest @ ..] => write!(f, "{error} (and {} more errors)", rest.len()),
+                [] => write!(f, "syntax error"),
+            },
+            Self::InvalidEscape(_) => write!(f, "invalid escape sequence"),
+        }
+    }
+}
+
+impl error::Error for DecodeError {}
+
+fn map(map: &ast::Map) -> Result<Value, DecodeError> {
+    let mut members = Vec::new();
+
+    for mapping in map.members() {
+        let (Some(key), Some(value)) = (mapping.key(), mapping.value()) else {
+            continue;
+        };
+
+        members.push(Member {
+            key: Key {
+                name: string(key.syntax())?,
+                span: key.range(),
+            },
+            value: self::value(&value)?,
+        });
+    }
+
+    Ok(Value {
+        kind: ValueKind::Map(Map { members }),
+        span: map.range(),
+    })
+}
+
+fn value(value: &ast::Value) -> Result<Value, DecodeError> {
+    let token = match value {
+        ast::Value::Map(inner) => return map(inner),
+        ast::Value::Array(array) => {
+            let values = array
+                .values()
+                .map(|value| self::value(&value))
+                .collect::<Result<_, _>>()?;
+
+            return Ok(Value {
+                kind: ValueKind::Array(values),
+                span: array.range(),
+            });
+        }
+        ast::Value::Scalar(token) => token,
+    };
+
+    let text = token.text();
+    let kind = match token.kind() {
+        TokenKind::Null => ValueKind::Null,
+        TokenKind::Bool => ValueKind::Bool(text == "true"),
+        TokenKind::Integer => ValueKind::Number(integer(text)),
+        TokenKind::Float => ValueKind::Number(Number::F64(text.parse().unwrap())),
+        _ => ValueKind::String(string(token)?),
+    };
+
+    Ok(Value {
+        kind,
+        span: token.range(),
+    })
+}
+
+/// Decode an integer, falling back to a float if it is too large.
+fn integer(text: &str) -> Number {
+    match text.parse::<i64>() {
+        Ok(n) if n < 0 => Number::I64(n),
+        Ok(n) => Number::U64(n as u64),
+        Err(_) => match text.parse::<u64>() {
+            Ok(n) => Number::U64(n),
+            Err(_) => Number::F64(text.parse().unwrap()),
+        },
+    }
+}
+
+/// Decode a string token of any kind.
+fn string(token: &SyntaxToken) -> Result<String, DecodeError> {
+    let text = token.text();
+
+    match token.kind() {
+        TokenKind::TextSingle | TokenKind::TextDouble => {
+            unescape(&text[1..text.len() - 1], token.range().start + 1)
+        }
+        TokenKind::TextMulti => {
+            let source = token.source();
+            let start = token.range().start;
+            let line_start = source[..start].rfind('\n').map_or(0, |x| x + 1);
+            let indent = source[line_start..start].chars().count();
+
+            Ok(dedent(&text[3..text.len() - 3], indent))
+        }
+        _ => Ok(text.to_string()),
+    }
+}
+
+/// Decode a single- or double-quoted string as written in the source,
+/// including its quotes.
+///
+/// Invalid escape sequences are reported with byte ranges within `text`.
+pub fn unquote(text: &str) -> Result<String, DecodeError> {
+    let quote = text.chars().next().filter(|c| matches!(c, '\'' | '"'));
+
+    match quote {
+        Some(quote) if text.len() > 1 && text.ends_with(quote) => {
+            unescape(&text[1..text.len() - 1], 1)
+        }
+        _ => Ok(text.to_string()),
+    }
+}
+
+/// Decode the escape sequences in the contents of a quoted string, which
+/// starts at byte `offset` in the source.
+fn unescape(text: &str, offset: usize) -> Result<String, DecodeError> {
+    let mut decoded = String::with_capacity(text.len());
+    let mut chars = text.char_indices();
+
+    while let Some((start, c)) = chars.next() {
+        if c != '\\' {
+            decoded.push(c);
+            continue;
+        }
+
+        let invalid = |len: usize| DecodeError::InvalidEscape(offset + start..offset + start + len);
+
+        let escaped = match chars.next() {
+            Some((_, c @ ('"' | '\'' | '\\' | '/'))) => c,
+            Some((_, 'b')) => '\u{8}',
+            Some((_, 'f')) => '\u{c}',
+            Some((_, 'n')) => '\n',
+            Some((_, 'r')) => '\r',
+            Some((_, 't')) => '\t',
+            Some((_, 'u')) => {
+                let high = hex(&text[start + 2..]).ok_or(invalid(6))?;
+                chars.nth(3);
+
+                match high {
+                    // A surrogate pair, escaped as `\uXXXX\uXXXX`.
+                    0xd800..=0xdbff => {
+                        let low = text[start + 6..]
+                            .strip_prefix("\\u")
+                            .and_then(hex)
+                            .filter(|low| (0xdc00..=0xdfff).contains(low))
+                            .ok_or(invalid(6))?;
+                        chars.nth(5);
+
+                        let c = 0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00);
+                        char::from_u32(c).unwrap()
+                    }
+                    c => char::from_u32(c).ok_or(invalid(6))?,
+                }
+            }
+            Some((_, c)) => return Err(invalid(1 + c.len_utf8())),
+            None => return Err(invalid(1)),
+        };
+
+        decoded.push(escaped);
+    }
+
+    Ok(decoded)
+}
+
+/// Parse the four hex digits at the start of `text`.
+fn hex(text: &str) -> Option<u32> {
+    let digits = text.get(..4)?;
+
+    match digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        true => u32::from_str_radix(digits, 16).ok(),
+        false => None,
+    }
+}
+
+/// Decode the contents of a multi-line string whose opening quotes are at
+/// column `indent`.
+///
+/// Following the Hjson spec, whitespace up to the column of the opening quotes
+/// is removed from each line, and so is the line break before the closing
+/// quotes. Escape sequences are not decoded.
+fn dedent(text: &str, indent: usize) -> String {
+    let trim_indent = |line: &str| {
+        let skip: usize = line
+            .chars()
+            .take(indent)
+            .take_while(|c| c.is_whitespace())
+            .map(char::len_utf8)
+            .sum();
+        line[skip..].to_string()
+    };
+
+    let mut lines = text.split('\n');
+    let first = lines.next().unwrap_or_default();
+
+    // Whitespace after the opening quotes is skipped, along with the line
+    // break if there is nothing else on the line.
+    let mut decoded: Vec<String> = Vec::new();
+    let first = first.trim_start();
+    if !first.is_empty() {
+        decoded.push(first.to_string());
+    }
+    decoded.extend(lines.map(trim_indent));
+
+    let mut decoded = decoded.join("\n").replace('\r', "");
+
+    // Whitespace before the closing quotes on their own line has already been
+    // removed as indentation.
+    if decoded.ends_with('\n') {
+        decoded.pop();
+    }
+
+    decoded
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parse_value;
+    use indoc::indoc;
+
+    #[test]
+    fn values() {
+        let value = parse_value(indoc! {r#"
+            a: null
+            b: [true, false]
+            c: [0, 42, -7, 1.5, 18446744073709551615, 1e400]
+            d: { e: "f" }
+        "#})
+        .unwrap();
+        let map = value.as_map().unwrap();
+
+        assert!(map.get("a").unwrap().is_null());
+        let b: Vec<_> = map.get("b").unwrap().as_array().unwrap().to_vec();
+        assert_eq!(b[0].as_bool(), Some(true));
+        assert_eq!(b[1].as_bool(), Some(false));
+
+        let c: Vec<_> = map
+            .get("c")
+            .unwrap()
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|value| value.as_number().unwrap())
+            .collect();
+        assert_eq!(
+            c,
+            [
+                Number::U64(0),
+                Number::U64(42),
+                Number::I64(-7),
+                Number::F64(1.5),
+                Number::U64(u64::MAX),
+                Number::F64(f64::INFINITY),
+            ]
+        );
+
+        let d = map.get("d").unwrap();
+        assert_eq!(d.span, 77..87);
+        assert_eq!(d.as_map().unwrap().get("e").unwrap().as_str(), Some("f"));
+    }
+
+    #[test]
+    fn spans() {
+        let value = parse_value("{ 'a b': [1, 'x y'] }").unwrap();
+        assert_eq!(value.span, 0..21);
+
+        let member = &value.as_map().unwrap().members[0];
+        assert_eq!(member.key.name, "a b");
+        assert_eq!(member.key.span, 2..7);
+        assert_eq!(member.value.span, 9..19);
+
+        let array = member.value.as_array().unwrap();
+        assert_eq!(array[0].span, 10..11);
+        assert_eq!(array[1].span, 13..18);
+        assert_eq!(array[1].as_str(), Some("x y"));
+    }
+
+    #[test]
+    fn duplicates() {
+        let value = parse_value("a: 1\na: 2").unwrap();
+        let map = value.as_map().unwrap();
+
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get("a").unwrap().as_number(), Some(Number::U64(2)));
+    }
+
+    #[test]
+    fn escapes() {
+        let cases = [
+            (r#""a\"b""#, "a\"b"),
+            (r#"'a\'b'"#, "a'b"),
+            (r#""a\\""#, "a\\"),
+            (r#"'a\\'"#, "a\\"),
+            (r#""\\\/\b\f\n\r\t""#, "\\/\u{8}\u{c}\n\r\t"),
+            (r#""éA""#, "éA"),
+            (r#""😀""#, "😀"),
+            (r#"'"'"#, "\""),
+        ];
+
+        for (text, expected) in cases {
+            let value = parse_value(&format!("a: {text}")).unwrap();
+            let map = value.as_map().unwrap();
+            assert_eq!(map.get("a").unwrap().as_str(), Some(expected));
+        }
+
+        let key = parse_value(r#""a": 1"#).unwrap();
+        assert_eq!(key.as_map().unwrap().members[0].key.name, "a");
+    }
+
+    #[test]
+    fn unquoted() {
+        assert_eq!(unquote(r#""a\u0062""#), Ok(String::from("ab")));
+        assert_eq!(unquote("'a\\'b'"), Ok(String::from("a'b")));
+        assert_eq!(unquote("a"), Ok(String::from("a")));
+        assert_eq!(unquote(r#""\x""#), Err(DecodeError::InvalidEscape(1..3)));
+    }
+
+    #[test]
+    fn invalid_escapes() {
+        let cases = [
+            (r#"a: "\q""#, 4..6),
+            (r#"a: "x\u12""#, 5..11),
+            (r#"a: "\ud83d""#, 4..10),
+            (r#"a: "\ud83dA""#, 4..10),
+        ];
+
+        for (input, range) in cases {
+            assert_eq!(parse_value(input), Err(DecodeError::InvalidEscape(range)));
+        }
+    }
+
+    #[test]
+    fn multi_line() {
+        let value = parse_value(indoc! {"
+            a: '''
+               first
+                 second
+
+               third
+               '''
+            b: '''inline'''
+            c: '''
+                 kept indentation
+               '''
+            d: '''  \\n not an escape
+            '''
+        "})
+        .unwrap();
+        let map = value.as_map().unwrap();
+        let get = |key| map.get(key).unwrap().as_str().unwrap();
+
+        assert_eq!(get("a"), "first\n  second\n\nthird");
+        assert_eq!(get("b"), "inline");
+        assert_eq!(get("c"), "  kept indentation");
+        assert_eq!(get("d"), "\\n not an escape");
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(parse_value("").unwrap().as_map(), Some(&Map::default()));
+
+        let Err(DecodeError::Syntax(errors)) = parse_value("a: [1,, 2]\nb: 3") else {
+            panic!("expected syntax errors");
+        };
+        assert_eq!(errors.len(), 1);
+
+        let err = parse_value("a: [1,, 2]\nb: [3,, 4]").unwrap_err();
+        assert!(err.to_string().ends_with(" (and 1 more error)"));
+        let err = parse_value("a: [1,, 2]\nb: [3,, 4]\nc: [5,, 6]").unwrap_err();
+        assert!(err.to_string().ends_with(" (and 2 more errors)"));
+    }
+
+    #[test]
+    fn serialize() {
+        let value = parse_value("a: [1, -2, 0.5, null]\nb: { c: true }\na: 'x y'").unwrap();
+
+        // Duplicate keys are kept in order.
+        assert_eq!(
+            crate::to_string(&value).unwrap(),
+            indoc! {"
+                a: [
+                  1
+                  -2
+                  0.5
+                  null
+                ]
+                b: {
+                  c: true
+                }
+                a: x y
+            "}
+        );
+    }
+}